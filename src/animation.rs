@@ -0,0 +1,202 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+// What an entity is doing, as far as its sprite is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimState {
+    Idle,
+    Walk,
+    Jump,
+    Fall,
+    Hurt,
+    Dead,
+}
+
+impl AnimState {
+    pub fn pick(dead: bool, hurt: bool, airborne: bool, velocity: Vec2) -> Self {
+        if dead {
+            AnimState::Dead
+        } else if hurt {
+            AnimState::Hurt
+        } else if airborne {
+            if velocity.y < 0.0 {
+                AnimState::Jump
+            } else {
+                AnimState::Fall
+            }
+        } else if velocity.x.abs() > 1.0 {
+            AnimState::Walk
+        } else {
+            AnimState::Idle
+        }
+    }
+
+    // Clips to try, in order, when a set has no clip for this state.
+    fn fallbacks(self) -> &'static [AnimState] {
+        use AnimState::*;
+        match self {
+            Idle => &[Idle, Walk],
+            Walk => &[Walk, Idle],
+            Jump => &[Jump, Fall, Idle, Walk],
+            Fall => &[Fall, Jump, Idle, Walk],
+            Hurt => &[Hurt, Idle, Walk],
+            Dead => &[Dead, Hurt, Idle, Walk],
+        }
+    }
+}
+
+// animations.json maps a sprite name to its clips, e.g.
+// {"enemy_slime": {"faces_left": true, "clips": {"walk": {"frames": [...]}}}}
+// Frame paths are relative to the assets root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationDef {
+    // Set when the source art looks left; sprites are flipped to match the
+    // entity's facing either way.
+    pub faces_left: bool,
+    pub clips: HashMap<AnimState, ClipDef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipDef {
+    pub frames: Vec<String>,
+    #[serde(default = "default_frame_time")]
+    pub frame_time: f32,
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+}
+
+fn default_frame_time() -> f32 {
+    0.1
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+pub struct AnimationClip {
+    pub frames: Vec<Texture2D>,
+    pub frame_time: f32,
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    pub fn frame_at(&self, time: f32) -> Option<&Texture2D> {
+        let count = self.frames.len();
+        if count == 0 {
+            return None;
+        }
+        let index = (time / self.frame_time.max(0.001)) as usize;
+        let index = if self.looping {
+            index % count
+        } else {
+            index.min(count - 1)
+        };
+        self.frames.get(index)
+    }
+}
+
+pub struct AnimationSet {
+    pub faces_left: bool,
+    pub clips: HashMap<AnimState, AnimationClip>,
+}
+
+impl AnimationSet {
+    // The clip for `state`, or the closest one the set has.
+    pub fn clip(&self, state: AnimState) -> Option<&AnimationClip> {
+        state
+            .fallbacks()
+            .iter()
+            .find_map(|s| self.clips.get(s))
+    }
+}
+
+// Per-entity playback position within a shared set.
+#[derive(Clone)]
+pub struct Animator {
+    pub set: Arc<AnimationSet>,
+    pub state: AnimState,
+    pub time: f32,
+}
+
+impl Animator {
+    pub fn new(set: Arc<AnimationSet>) -> Self {
+        Self {
+            set,
+            state: AnimState::Idle,
+            time: 0.0,
+        }
+    }
+
+    // Switches clip (restarting it) when the state changes, then advances.
+    pub fn update(&mut self, state: AnimState, dt: f32) {
+        if state != self.state {
+            self.state = state;
+            self.time = 0.0;
+        } else {
+            self.time += dt;
+        }
+    }
+
+    pub fn frame(&self) -> Option<&Texture2D> {
+        self.set
+            .clip(self.state)
+            .and_then(|clip| clip.frame_at(self.time))
+    }
+
+    pub fn has_clip(&self, state: AnimState) -> bool {
+        self.set.clips.contains_key(&state)
+    }
+}
+
+// Reads animations.json. A missing file just means no animations.
+pub fn load_animation_defs(path: &str) -> Result<HashMap<String, AnimationDef>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Warning: could not read {path}: {e}");
+            return Ok(HashMap::new());
+        }
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {path}: {e}"))
+}
+
+// Builds the sets from their definitions, getting each frame's texture from
+// `load` (called with the full path).
+pub fn build_animation_sets(
+    root: &str,
+    defs: HashMap<String, AnimationDef>,
+    mut load: impl FnMut(&str) -> Result<Texture2D, String>,
+) -> Result<HashMap<String, Arc<AnimationSet>>, String> {
+    let mut sets = HashMap::new();
+    for (name, def) in defs {
+        let mut clips = HashMap::new();
+        for (state, clip) in def.clips {
+            let frames = clip
+                .frames
+                .iter()
+                .map(|frame| load(&format!("{root}/{frame}")))
+                .collect::<Result<Vec<_>, _>>()?;
+            clips.insert(
+                state,
+                AnimationClip {
+                    frames,
+                    frame_time: clip.frame_time,
+                    looping: clip.looping,
+                },
+            );
+        }
+        sets.insert(
+            name,
+            Arc::new(AnimationSet {
+                faces_left: def.faces_left,
+                clips,
+            }),
+        );
+    }
+    Ok(sets)
+}
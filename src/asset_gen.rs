@@ -25,6 +25,11 @@ pub fn generate_placeholder_assets(
     ensure_dir(base.join("sprites/goals")).map_err(to_string)?;
     ensure_dir(base.join("tiles/platforms")).map_err(to_string)?;
     ensure_dir(base.join("backgrounds")).map_err(to_string)?;
+    ensure_dir(base.join("backgrounds/layers")).map_err(to_string)?;
+    ensure_dir(base.join("sprites/projectiles")).map_err(to_string)?;
+    ensure_dir(base.join("sprites/hazards")).map_err(to_string)?;
+    ensure_dir(base.join("sprites/checkpoints")).map_err(to_string)?;
+    ensure_dir(base.join("sprites/powerups")).map_err(to_string)?;
     ensure_dir(base.join("sounds")).map_err(to_string)?;
 
     // Clamp dimensions to avoid invalid sizes
@@ -54,8 +59,21 @@ pub fn generate_placeholder_assets(
         bottom: [u8; 4],
     }
 
+    // A tileable hill silhouette over a transparent sky.
+    struct HillsTask {
+        path: PathBuf,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+        // Mean hill top as a fraction of the height down from the top.
+        base: f32,
+        amplitude: f32,
+        waves: [(u32, f32); 2],
+    }
+
     let mut solid_tasks: Vec<SolidTask> = Vec::new();
     let mut gradient_tasks: Vec<GradientTask> = Vec::new();
+    let mut hills_tasks: Vec<HillsTask> = Vec::new();
 
     // Players
     solid_tasks.push(SolidTask {
@@ -142,6 +160,86 @@ pub fn generate_placeholder_assets(
         });
     }
 
+    // Projectiles. Rolled after everything else so existing seeds keep
+    // their colors.
+    solid_tasks.push(SolidTask {
+        path: base.join("sprites/projectiles/projectile_player.png"),
+        width: 8,
+        height: 8,
+        color: random_color(&mut rng, palettes.player_primary, 30),
+    });
+    solid_tasks.push(SolidTask {
+        path: base.join("sprites/projectiles/projectile_enemy.png"),
+        width: 8,
+        height: 8,
+        color: random_color(&mut rng, palettes.enemy_primary, 30),
+    });
+
+    // Hazards, named so the generator can tell them apart.
+    for (name, width, height, color) in [
+        ("spikes_1", 32, 16, [170, 170, 180]),
+        ("saw_1", 32, 32, [200, 200, 210]),
+        ("lava_1", 64, 32, [230, 80, 20]),
+    ] {
+        solid_tasks.push(SolidTask {
+            path: base.join(format!("sprites/hazards/{name}.png")),
+            width,
+            height,
+            color: random_color(&mut rng, color, 20),
+        });
+    }
+
+    // Checkpoint flag
+    solid_tasks.push(SolidTask {
+        path: base.join("sprites/checkpoints/checkpoint_1.png"),
+        width: 24,
+        height: 48,
+        color: random_color(&mut rng, palettes.goal_secondary, 20),
+    });
+
+    // Power-ups, named after their kind like the hazards
+    for (name, color) in [
+        ("speed_1", [90, 190, 240]),
+        ("invincibility_1", [250, 210, 40]),
+        ("high_jump_1", [60, 220, 60]),
+        ("magnet_1", [170, 90, 220]),
+        ("double_damage_1", [240, 140, 30]),
+        ("shield_1", [150, 200, 250]),
+    ] {
+        solid_tasks.push(SolidTask {
+            path: base.join(format!("sprites/powerups/{name}.png")),
+            width: collectible_size,
+            height: collectible_size,
+            color: random_color(&mut rng, color, 20),
+        });
+    }
+
+    // Parallax layers for backgrounds.json: sky, far hills, near hills
+    gradient_tasks.push(GradientTask {
+        path: base.join("backgrounds/layers/sky.png"),
+        width: bg_width,
+        height: bg_height,
+        top: random_color(&mut rng, palettes.bg_top, 20),
+        bottom: random_color(&mut rng, palettes.bg_bottom, 20),
+    });
+    for (name, color, base_y, amplitude, waves) in [
+        ("far_hills", mix(palettes.bg_bottom, palettes.platform_secondary, 0.35), 0.55, 0.12, 2),
+        ("near_hills", mix(palettes.bg_bottom, palettes.platform_primary, 0.7), 0.72, 0.1, 3),
+    ] {
+        hills_tasks.push(HillsTask {
+            path: base.join(format!("backgrounds/layers/{name}.png")),
+            width: bg_width,
+            height: bg_height,
+            color: random_color(&mut rng, color, 15),
+            base: base_y,
+            amplitude,
+            waves: [
+                (waves, rng.gen_range(0.0..2.0 * PI)),
+                (waves * 2 + 1, rng.gen_range(0.0..2.0 * PI)),
+            ],
+        });
+    }
+
     // Run solid sprite tasks in parallel.
     let mut handles = Vec::new();
     for task in solid_tasks {
@@ -180,6 +278,26 @@ pub fn generate_placeholder_assets(
         }
     }
 
+    let mut hills_handles = Vec::new();
+    for task in hills_tasks {
+        hills_handles.push(thread::spawn(move || {
+            save_hills(
+                task.path,
+                task.width,
+                task.height,
+                task.color,
+                task.base,
+                task.amplitude,
+                task.waves,
+            )
+        }));
+    }
+    for handle in hills_handles {
+        handle
+            .join()
+            .map_err(|_| "Background generation thread panicked".to_string())??;
+    }
+
     generate_placeholder_sounds(&base.join("sounds"), rules)?;
 
     Ok(())
@@ -224,6 +342,36 @@ fn save_vertical_gradient(
     Ok(())
 }
 
+// Whole numbers of each wave fit across the width, so the image tiles.
+fn save_hills(
+    path: impl AsRef<Path>,
+    width: u32,
+    height: u32,
+    color: [u8; 4],
+    base: f32,
+    amplitude: f32,
+    waves: [(u32, f32); 2],
+) -> Result<(), String> {
+    let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32 * 2.0 * PI;
+        let wave = (u * waves[0].0 as f32 + waves[0].1).sin() * 0.65
+            + (u * waves[1].0 as f32 + waves[1].1).sin() * 0.35;
+        let top = (base - amplitude * wave) * height as f32;
+        if (y as f32) < top {
+            Rgba([0, 0, 0, 0])
+        } else {
+            Rgba(color)
+        }
+    });
+    img.save(path).map_err(to_string)?;
+    Ok(())
+}
+
+// `a` blended towards `b` by `t`.
+fn mix(a: [u8; 3], b: [u8; 3], t: f32) -> [u8; 3] {
+    [0, 1, 2].map(|i| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8)
+}
+
 fn random_color(rng: &mut StdRng, base: [u8; 3], variance: u8) -> [u8; 4] {
     let mut out = [0u8; 4];
     for i in 0..3 {
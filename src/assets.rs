@@ -1,27 +1,53 @@
+use crate::animation::{build_animation_sets, load_animation_defs, AnimationSet};
+use crate::background::{load_backgrounds_file, BackgroundsFile};
+use crate::lighting::{load_lighting_file, LightingFile};
+use crate::particles::{
+    build_particle_effects, effect_texture_paths, load_effects_file, ParticleEffects,
+};
+use macroquad::miniquad;
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SpriteKind {
     Player,
     Enemy,
     Background,
+    // Parallax layers stacked by backgrounds.json.
+    BackgroundLayer,
     Platform,
     Collectible,
     GoalCollectible,
+    Projectile,
+    Hazard,
+    Checkpoint,
+    PowerUp,
 }
 
 #[derive(Clone)]
 pub struct SpriteAsset {
     pub name: String,
     pub texture: Texture2D,
+    // Region of `texture` for sprites cut from an atlas; `None` for loose
+    // PNGs. `size` is the size of this region.
+    pub source: Option<Rect>,
+    pub size: Vec2,
     pub kind: SpriteKind,
 }
 
 #[derive(Clone)]
 pub struct Assets {
     pub sprites: Vec<SpriteAsset>,
+    // Keyed by sprite name; sprites without an entry are drawn static.
+    pub animations: HashMap<String, Arc<AnimationSet>>,
+    pub particle_effects: ParticleEffects,
+    pub backgrounds: BackgroundsFile,
+    pub lighting: LightingFile,
 }
 
 impl Assets {
@@ -41,49 +67,336 @@ impl Assets {
             .iter()
             .find(|s| s.kind == kind && s.name.eq_ignore_ascii_case(name))
     }
+
+    pub fn animation(&self, sprite_name: &str) -> Option<Arc<AnimationSet>> {
+        self.animations.get(sprite_name).cloned()
+    }
 }
 
+const SPRITE_DIRS: &[(&str, SpriteKind)] = &[
+    ("sprites/player", SpriteKind::Player),
+    ("sprites/enemies", SpriteKind::Enemy),
+    ("sprites/collectibles", SpriteKind::Collectible),
+    ("sprites/goals", SpriteKind::GoalCollectible),
+    ("tiles/platforms", SpriteKind::Platform),
+    ("backgrounds", SpriteKind::Background),
+    ("backgrounds/layers", SpriteKind::BackgroundLayer),
+    ("sprites/projectiles", SpriteKind::Projectile),
+    ("sprites/hazards", SpriteKind::Hazard),
+    ("sprites/checkpoints", SpriteKind::Checkpoint),
+    ("sprites/powerups", SpriteKind::PowerUp),
+];
+
 pub async fn load_assets(root: &str) -> Result<Assets, String> {
     let mut sprites = Vec::new();
 
-    load_sprites_for_kind(root, "sprites/player", SpriteKind::Player, &mut sprites).await?;
-    load_sprites_for_kind(root, "sprites/enemies", SpriteKind::Enemy, &mut sprites).await?;
-    load_sprites_for_kind(
-        root,
-        "sprites/collectibles",
-        SpriteKind::Collectible,
-        &mut sprites,
-    )
-    .await?;
-    load_sprites_for_kind(
-        root,
-        "sprites/goals",
-        SpriteKind::GoalCollectible,
-        &mut sprites,
-    )
-    .await?;
-    load_sprites_for_kind(root, "tiles/platforms", SpriteKind::Platform, &mut sprites).await?;
-    load_sprites_for_kind(root, "backgrounds", SpriteKind::Background, &mut sprites).await?;
-
-    Ok(Assets { sprites })
-}
-
-async fn load_sprites_for_kind(
-    root: &str,
-    subdir: &str,
-    kind: SpriteKind,
-    sprites: &mut Vec<SpriteAsset>,
-) -> Result<(), String> {
+    for (subdir, kind) in SPRITE_DIRS {
+        for (name, path) in sprite_paths(root, subdir)? {
+            let texture = load_texture(&path)
+                .await
+                .map_err(|e| format!("Failed to load texture {path}: {e:?}"))?;
+            texture.set_filter(FilterMode::Nearest);
+            let size = texture.size();
+
+            sprites.push(SpriteAsset {
+                name,
+                texture,
+                source: None,
+                size,
+                kind: *kind,
+            });
+        }
+    }
+
+    // Atlas sprites come after loose ones so existing seeds pick the same
+    // sprites as before.
+    for entry in load_atlas_entries(&atlases_path(root))? {
+        let atlas = load_atlas(root, &entry)?;
+        let texture = load_texture(&atlas.image_path)
+            .await
+            .map_err(|e| format!("Failed to load atlas image {}: {e:?}", atlas.image_path))?;
+        texture.set_filter(FilterMode::Nearest);
+        sprites.extend(atlas.sprites(&entry, &texture));
+    }
+
+    let defs = load_animation_defs(&animations_path(root))?;
+    let mut frames: HashMap<String, Texture2D> = HashMap::new();
+    for frame in defs
+        .values()
+        .flat_map(|def| def.clips.values())
+        .flat_map(|clip| &clip.frames)
+    {
+        let path = format!("{root}/{frame}");
+        if frames.contains_key(&path) {
+            continue;
+        }
+        let texture = load_texture(&path)
+            .await
+            .map_err(|e| format!("Failed to load animation frame {path}: {e:?}"))?;
+        texture.set_filter(FilterMode::Nearest);
+        frames.insert(path, texture);
+    }
+    let animations = build_animation_sets(root, defs, |path| {
+        frames
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("Animation frame {path} was not loaded"))
+    })?;
+
+    let effects_file = load_effects_file(&effects_path(root))?;
+    let mut particle_textures: HashMap<String, Texture2D> = HashMap::new();
+    for texture_path in effect_texture_paths(&effects_file) {
+        let path = format!("{root}/{texture_path}");
+        if particle_textures.contains_key(&path) {
+            continue;
+        }
+        let texture = load_texture(&path)
+            .await
+            .map_err(|e| format!("Failed to load particle texture {path}: {e:?}"))?;
+        texture.set_filter(FilterMode::Nearest);
+        particle_textures.insert(path, texture);
+    }
+    let particle_effects = build_particle_effects(root, effects_file, |path| {
+        particle_textures
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("Particle texture {path} was not loaded"))
+    })?;
+
+    Ok(Assets {
+        sprites,
+        animations,
+        particle_effects,
+        backgrounds: load_backgrounds_file(&backgrounds_path(root))?,
+        lighting: load_lighting_file(&lighting_path(root))?,
+    })
+}
+
+// Loads the same sprite set as `load_assets`, but only reads image sizes
+// from disk and never touches the GPU. Textures are inert placeholders, so
+// scenes built from these assets can be simulated but not drawn.
+pub fn load_assets_headless(root: &str) -> Result<Assets, String> {
+    let mut sprites = Vec::new();
+
+    for (subdir, kind) in SPRITE_DIRS {
+        for (name, path) in sprite_paths(root, subdir)? {
+            let (w, h) = image::image_dimensions(&path)
+                .map_err(|e| format!("Failed to read image size of {path}: {e}"))?;
+
+            sprites.push(SpriteAsset {
+                name,
+                texture: headless_texture(),
+                source: None,
+                size: vec2(w as f32, h as f32),
+                kind: *kind,
+            });
+        }
+    }
+
+    for entry in load_atlas_entries(&atlases_path(root))? {
+        let atlas = load_atlas(root, &entry)?;
+        if !Path::new(&atlas.image_path).is_file() {
+            return Err(format!("Atlas image {} does not exist", atlas.image_path));
+        }
+        sprites.extend(atlas.sprites(&entry, &headless_texture()));
+    }
+
+    let defs = load_animation_defs(&animations_path(root))?;
+    let animations = build_animation_sets(root, defs, |path| {
+        if Path::new(path).is_file() {
+            Ok(headless_texture())
+        } else {
+            Err(format!("Animation frame {path} does not exist"))
+        }
+    })?;
+
+    let effects_file = load_effects_file(&effects_path(root))?;
+    let particle_effects = build_particle_effects(root, effects_file, |path| {
+        if Path::new(path).is_file() {
+            Ok(headless_texture())
+        } else {
+            Err(format!("Particle texture {path} does not exist"))
+        }
+    })?;
+
+    Ok(Assets {
+        sprites,
+        animations,
+        particle_effects,
+        backgrounds: load_backgrounds_file(&backgrounds_path(root))?,
+        lighting: load_lighting_file(&lighting_path(root))?,
+    })
+}
+
+fn animations_path(root: &str) -> String {
+    format!("{root}/config/animations.json")
+}
+
+fn effects_path(root: &str) -> String {
+    format!("{root}/config/effects.json")
+}
+
+fn backgrounds_path(root: &str) -> String {
+    format!("{root}/config/backgrounds.json")
+}
+
+fn lighting_path(root: &str) -> String {
+    format!("{root}/config/lighting.json")
+}
+
+fn atlases_path(root: &str) -> String {
+    format!("{root}/config/atlases.json")
+}
+
+// One entry of atlases.json: a Kenney-style TextureAtlas XML file (path
+// relative to the assets root) whose sub-textures all become sprites of
+// `kind`. `names` limits it to those sub-textures (without ".png").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasEntry {
+    pub path: String,
+    pub kind: SpriteKind,
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+struct SubTexture {
+    name: String,
+    rect: Rect,
+}
+
+struct TextureAtlas {
+    image_path: String,
+    sub_textures: Vec<SubTexture>,
+}
+
+impl TextureAtlas {
+    // Every selected sub-texture as a sprite sharing `texture`.
+    fn sprites(&self, entry: &AtlasEntry, texture: &Texture2D) -> Vec<SpriteAsset> {
+        self.sub_textures
+            .iter()
+            .filter(|sub| entry.names.is_empty() || entry.names.contains(&sub.name))
+            .map(|sub| SpriteAsset {
+                name: sub.name.clone(),
+                texture: texture.clone(),
+                source: Some(sub.rect),
+                size: vec2(sub.rect.w, sub.rect.h),
+                kind: entry.kind,
+            })
+            .collect()
+    }
+}
+
+// A missing atlases.json just means no atlases.
+fn load_atlas_entries(path: &str) -> Result<Vec<AtlasEntry>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return Ok(Vec::new()),
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {path}: {e}"))
+}
+
+fn load_atlas(root: &str, entry: &AtlasEntry) -> Result<TextureAtlas, String> {
+    let path = format!("{root}/{}", entry.path);
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read atlas {path}: {e}"))?;
+    let (image, sub_textures) =
+        parse_atlas_xml(&text).map_err(|e| format!("Failed to parse atlas {path}: {e}"))?;
+
+    // imagePath is relative to the XML file. Some packs ship it stale, so
+    // fall back to the PNG named like the XML file.
+    let xml_path = Path::new(&path);
+    let dir = xml_path.parent().unwrap_or(Path::new(""));
+    let mut image_path = dir.join(image);
+    if !image_path.is_file() {
+        image_path = xml_path.with_extension("png");
+    }
+    let image_path = image_path
+        .to_str()
+        .ok_or_else(|| "Non-UTF8 path in atlas".to_string())?
+        .to_string();
+
+    Ok(TextureAtlas {
+        image_path,
+        sub_textures,
+    })
+}
+
+// Reads <TextureAtlas imagePath="..."> and its <SubTexture name x y width
+// height/> children. Anything else in the file is ignored.
+fn parse_atlas_xml(text: &str) -> Result<(String, Vec<SubTexture>), String> {
+    let mut image = None;
+    let mut sub_textures = Vec::new();
+
+    for tag in text.split('<').skip(1) {
+        let tag = tag.split('>').next().unwrap_or("");
+        let attrs = xml_attributes(tag);
+        let attr = |key: &str| attrs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        let number = |key: &str| -> Result<f32, String> {
+            attr(key)
+                .ok_or_else(|| format!("SubTexture is missing '{key}'"))?
+                .parse::<f32>()
+                .map_err(|e| format!("Bad '{key}' in SubTexture: {e}"))
+        };
+
+        if tag.starts_with("TextureAtlas") {
+            image = attr("imagePath").map(str::to_string);
+        } else if tag.starts_with("SubTexture") {
+            let name = attr("name").ok_or("SubTexture is missing 'name'")?;
+            let name = name.strip_suffix(".png").unwrap_or(name).to_string();
+            let rect = Rect::new(
+                number("x")?,
+                number("y")?,
+                number("width")?,
+                number("height")?,
+            );
+            sub_textures.push(SubTexture { name, rect });
+        }
+    }
+
+    let image = image.ok_or("no TextureAtlas element with an imagePath")?;
+    Ok((image, sub_textures))
+}
+
+// The key="value" pairs of one tag's contents.
+fn xml_attributes(tag: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = tag;
+    while let Some(eq) = rest.find("=\"") {
+        let key = rest[..eq]
+            .rsplit(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or("");
+        let value_start = eq + 2;
+        let Some(len) = rest[value_start..].find('"') else {
+            break;
+        };
+        attrs.push((key, &rest[value_start..value_start + len]));
+        rest = &rest[value_start + len + 1..];
+    }
+    attrs
+}
+
+// A texture handle that needs no GPU. Drawing it shows nothing useful, so
+// it is only for things that are never drawn or when running headless.
+pub fn headless_texture() -> Texture2D {
+    Texture2D::from_miniquad_texture(miniquad::TextureId::from_raw_id(
+        miniquad::RawId::OpenGl(0),
+    ))
+}
+
+// Returns (name, path) for every PNG in `root/subdir`.
+fn sprite_paths(root: &str, subdir: &str) -> Result<Vec<(String, String)>, String> {
     let dir = format!("{root}/{subdir}");
 
     let entries = match fs::read_dir(&dir) {
         Ok(entries) => entries,
         Err(e) => {
             eprintln!("Warning: could not read directory {dir}: {e}");
-            return Ok(());
+            return Ok(Vec::new());
         }
     };
 
+    let mut paths = Vec::new();
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry in {dir}: {e}"))?;
         let path = entry.path();
@@ -101,19 +414,17 @@ async fn load_sprites_for_kind(
             .ok_or_else(|| "Non-UTF8 path in assets directory".to_string())?
             .to_string();
 
-        let texture = load_texture(&path_str)
-            .await
-            .map_err(|e| format!("Failed to load texture {path_str}: {e:?}"))?;
-        texture.set_filter(FilterMode::Nearest);
-
         let name = Path::new(&path_str)
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("unnamed")
             .to_string();
 
-        sprites.push(SpriteAsset { name, texture, kind });
+        paths.push((name, path_str));
     }
 
-    Ok(())
+    // Directory order is platform dependent; sort so seeded runs pick the
+    // same sprites everywhere.
+    paths.sort();
+    Ok(paths)
 }
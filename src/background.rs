@@ -0,0 +1,138 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::assets::SpriteAsset;
+
+// backgrounds.json stacks background layers, back to front. A theme's stack
+// under "themes" replaces "layers" when the rules' theme matches, e.g.
+// {"layers": [{"sprite": "sky", "scroll": 0.0},
+//             {"sprite": "far_hills", "scroll": 0.25}],
+//  "themes": {"night": [{"sprite": "stars", "scroll": 0.0}]}}
+// Scenes without a stack draw a single background as before.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundsFile {
+    pub layers: Vec<BackgroundLayerDef>,
+    pub themes: HashMap<String, Vec<BackgroundLayerDef>>,
+}
+
+impl BackgroundsFile {
+    pub fn for_theme(&self, theme: &str) -> &[BackgroundLayerDef] {
+        self.themes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(theme))
+            .map_or(&self.layers, |(_, layers)| layers)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundLayerDef {
+    // A sprite from backgrounds/layers or backgrounds.
+    pub sprite: String,
+    // How fast the layer moves with the camera: 0 stays put, 1 moves with
+    // the world.
+    pub scroll: f32,
+    pub tile_x: bool,
+    pub tile_y: bool,
+    // Top of the layer below the top of the world, in pixels.
+    pub offset_y: f32,
+    // Horizontal drift in pixels per second, e.g. for clouds.
+    pub auto_scroll: f32,
+    // Drawn height in pixels, keeping the sprite's aspect ratio; 0 fills
+    // the view's height.
+    pub height: f32,
+}
+
+impl Default for BackgroundLayerDef {
+    fn default() -> Self {
+        Self {
+            sprite: String::new(),
+            scroll: 0.5,
+            tile_x: true,
+            tile_y: false,
+            offset_y: 0.0,
+            auto_scroll: 0.0,
+            height: 0.0,
+        }
+    }
+}
+
+// A missing backgrounds.json just means single backgrounds.
+pub fn load_backgrounds_file(path: &str) -> Result<BackgroundsFile, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return Ok(BackgroundsFile::default()),
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {path}: {e}"))
+}
+
+#[derive(Clone)]
+pub struct BackgroundLayer {
+    pub def: BackgroundLayerDef,
+    pub texture: Texture2D,
+    pub source: Option<Rect>,
+    pub size: Vec2,
+}
+
+impl BackgroundLayer {
+    pub fn new(def: BackgroundLayerDef, sprite: &SpriteAsset) -> Self {
+        Self {
+            def,
+            texture: sprite.texture.clone(),
+            source: sprite.source,
+            size: sprite.size,
+        }
+    }
+
+    // Draws the copies overlapping `visible`, in the layer's own scrolled
+    // space; `view_height` is what a zero `height` fills.
+    pub fn draw(&self, visible: Rect, view_height: f32, time: f32) {
+        let def = &self.def;
+        let height = if def.height > 0.0 {
+            def.height
+        } else {
+            view_height
+        };
+        let width = height * self.size.x / self.size.y.max(1.0);
+        if width < 1.0 || height < 1.0 {
+            return;
+        }
+
+        let drift = time * def.auto_scroll;
+        let (x0, x1) = if def.tile_x {
+            let x = ((visible.x - drift) / width).floor() * width + drift;
+            (x, visible.right())
+        } else {
+            (drift, drift + width)
+        };
+        let (y0, y1) = if def.tile_y {
+            let y = ((visible.y - def.offset_y) / height).floor() * height + def.offset_y;
+            (y, visible.bottom())
+        } else {
+            (def.offset_y, def.offset_y + height)
+        };
+
+        let mut y = y0;
+        while y < y1 {
+            let mut x = x0;
+            while x < x1 {
+                draw_texture_ex(
+                    &self.texture,
+                    x,
+                    y,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(width, height)),
+                        source: self.source,
+                        ..Default::default()
+                    },
+                );
+                x += width;
+            }
+            y += height;
+        }
+    }
+}
@@ -0,0 +1,118 @@
+// Steps a generated level without a window or audio device, so seeded levels
+// can be driven from CI machines and balance scripts.
+//
+// Usage:
+//   cargo run --bin simulate -- [--seed N] [--level N] [--frames N]
+//       [--script input.json] [--rules rules.json] [--screen WxH]
+//
+// Each frame is one fixed physics step (`physics_tick_rate` from the rules),
+// so runs are identical to the game loop for the same seed and input.
+// Prints a JSON summary of the run on stdout.
+use engine::assets::load_assets_headless;
+use engine::events::RunStats;
+use engine::generator::{generate_scene_for_seed, load_rules};
+use engine::input::ScriptedInput;
+use engine::scene::EntityKind;
+use macroquad::prelude::vec2;
+
+struct Options {
+    seed: Option<u64>,
+    level: u32,
+    frames: u32,
+    script: Option<String>,
+    rules_path: String,
+    screen: (f32, f32),
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let opts = parse_args(std::env::args().skip(1))?;
+
+    let rules = load_rules(&opts.rules_path);
+    let seed = opts.seed.or(rules.seed).unwrap_or(0);
+    let assets = load_assets_headless("assets")?;
+
+    let world_size = vec2(
+        opts.screen.0 * rules.world_width_screens.max(1.0),
+        opts.screen.1 * rules.world_height_screens.max(1.0),
+    );
+    let mut scene = generate_scene_for_seed(&assets, &rules, opts.level, world_size, seed);
+
+    let mut input = match &opts.script {
+        Some(path) => ScriptedInput::load(path)?,
+        None => ScriptedInput::default(),
+    };
+
+    let mut stats = RunStats::default();
+    let mut frames_run: u32 = 0;
+    while frames_run < opts.frames && !scene.player_dead {
+        scene.update(scene.fixed_dt, &mut input);
+        for event in scene.take_events() {
+            stats.record(&event);
+        }
+        frames_run += 1;
+    }
+
+    let count =
+        |kind: fn(&EntityKind) -> bool| scene.entities.iter().filter(|e| kind(&e.kind)).count();
+    let player = scene.player_position();
+    let summary = serde_json::json!({
+        "seed": seed,
+        "level": opts.level,
+        "frames": frames_run,
+        "time": frames_run as f32 * scene.fixed_dt,
+        "score": scene.score,
+        "enemies_defeated": scene.enemies_defeated,
+        "player_health": scene.player_health,
+        "player_dead": scene.player_dead,
+        "goal_reached": scene.goal_reached,
+        "player_position": player.map(|p| [p.x, p.y]),
+        "enemies": count(|k| matches!(k, EntityKind::Enemy)),
+        "collectibles": count(|k| matches!(k, EntityKind::Collectible)),
+        "hazards": count(|k| matches!(k, EntityKind::Hazard)),
+        "projectiles": scene.projectiles.len(),
+        "platforms": scene.platforms.len(),
+        "stats": stats,
+    });
+    println!("{summary}");
+
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options {
+        seed: None,
+        level: 1,
+        frames: 600,
+        script: None,
+        rules_path: "assets/config/rules.json".to_string(),
+        screen: (1280.0, 720.0),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--seed" => opts.seed = Some(parse_num(&value()?)?),
+            "--level" => opts.level = parse_num::<u32>(&value()?)?.max(1),
+            "--frames" => opts.frames = parse_num(&value()?)?,
+            "--script" => opts.script = Some(value()?),
+            "--rules" => opts.rules_path = value()?,
+            "--screen" => {
+                let v = value()?;
+                let (w, h) = v
+                    .split_once('x')
+                    .ok_or_else(|| format!("Expected WxH for --screen, got {v}"))?;
+                opts.screen = (parse_num(w)?, parse_num(h)?);
+            }
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
+    }
+
+    Ok(opts)
+}
+
+fn parse_num<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.trim().parse().map_err(|_| format!("Invalid number: {s}"))
+}
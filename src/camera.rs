@@ -0,0 +1,227 @@
+use macroquad::prelude::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraConfig {
+    // Seconds the critically damped follow takes to catch up; 0 snaps.
+    pub smooth_time: f32,
+    // Half-size of the box around the view centre the target can move in
+    // without the camera following.
+    pub dead_zone: Vec2,
+    // The view leads the target by this many seconds of its horizontal
+    // velocity, up to `look_ahead_max` pixels.
+    pub look_ahead: f32,
+    pub look_ahead_max: f32,
+    // World pixels per screen pixel is 1 / zoom.
+    pub zoom: f32,
+    pub shake_enabled: bool,
+    // Trauma lost per second.
+    pub shake_decay: f32,
+    // Shake at full trauma; it scales with trauma squared.
+    pub shake_max_offset: f32,
+    pub shake_max_angle: f32,
+    pub shake_frequency: f32,
+}
+
+impl Default for CameraConfig {
+    // A camera locked onto its target, as before these options existed.
+    fn default() -> Self {
+        Self {
+            smooth_time: 0.0,
+            dead_zone: Vec2::ZERO,
+            look_ahead: 0.0,
+            look_ahead_max: 0.0,
+            zoom: 1.0,
+            shake_enabled: false,
+            shake_decay: 1.5,
+            shake_max_offset: 12.0,
+            shake_max_angle: 2.0,
+            shake_frequency: 25.0,
+        }
+    }
+}
+
+// A 2D view into the world: follows a target inside a bounded world and
+// shakes on demand. `position` is the centre of the view in world space.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub config: CameraConfig,
+    pub position: Vec2,
+    velocity: Vec2,
+    // Where the dead zone is centred; lags the target by up to the zone.
+    focus: Vec2,
+    // 0-1; adds up from hits and decays over time.
+    trauma: f32,
+    time: f32,
+}
+
+impl Camera {
+    pub fn new(config: CameraConfig, position: Vec2) -> Self {
+        Self {
+            config,
+            position,
+            velocity: Vec2::ZERO,
+            focus: position,
+            trauma: 0.0,
+            time: 0.0,
+        }
+    }
+
+    // Jumps straight to `target`, dropping any follow momentum.
+    pub fn snap_to(&mut self, target: Vec2) {
+        self.position = target;
+        self.focus = target;
+        self.velocity = Vec2::ZERO;
+    }
+
+    // Moves the view by `delta` world pixels, e.g. for panning by hand.
+    pub fn pan(&mut self, delta: Vec2) {
+        self.position += delta;
+        self.focus = self.position;
+        self.velocity = Vec2::ZERO;
+    }
+
+    // Follows `target` moving at `target_velocity`, keeping the view inside
+    // `world` (a world smaller than the view pins to its top-left). Targets
+    // that teleport further than a view away, such as a new level's start,
+    // are snapped to.
+    pub fn follow(&mut self, dt: f32, target: Vec2, target_velocity: Vec2, world: Rect) {
+        let c = self.config;
+        let view = self.view_size();
+        if (target - self.focus).abs().cmpgt(view).any() {
+            self.snap_to(target);
+        }
+
+        let dz = c.dead_zone.max(Vec2::ZERO);
+        self.focus = self.focus.clamp(target - dz, target + dz);
+        let lead = (target_velocity.x * c.look_ahead).clamp(-c.look_ahead_max, c.look_ahead_max);
+        let desired = self.focus + vec2(lead, 0.0);
+
+        if c.smooth_time > 0.0 && dt > 0.0 {
+            let velocity = &mut self.velocity;
+            self.position = smooth_damp(self.position, desired, velocity, c.smooth_time, dt);
+        } else {
+            self.position = desired;
+            self.velocity = Vec2::ZERO;
+        }
+        self.position = clamp_to_world(self.position, view, world);
+
+        self.time += dt;
+        self.trauma = (self.trauma - c.shake_decay * dt).max(0.0);
+    }
+
+    // Adds screen shake; `amount` is trauma from 0 to 1.
+    pub fn add_trauma(&mut self, amount: f32) {
+        if self.config.shake_enabled {
+            self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+        }
+    }
+
+    // World size of the screen at the current zoom.
+    pub fn view_size(&self) -> Vec2 {
+        vec2(screen_width(), screen_height()) / self.config.zoom.max(0.01)
+    }
+
+    // The world rect in view, ignoring shake.
+    pub fn view_rect(&self) -> Rect {
+        let size = self.view_size();
+        Rect::new(
+            self.position.x - size.x / 2.0,
+            self.position.y - size.y / 2.0,
+            size.x,
+            size.y,
+        )
+    }
+
+    // `view_rect` grown to cover the current shake, for culling.
+    pub fn visible_rect(&self) -> Rect {
+        let (offset, angle) = self.shake();
+        let view = self.view_rect();
+        let margin = offset.length() + angle.abs().to_radians() * view.size().length();
+        Rect::new(
+            view.x - margin,
+            view.y - margin,
+            view.w + margin * 2.0,
+            view.h + margin * 2.0,
+        )
+    }
+
+    // For `set_camera`, with the shake applied.
+    pub fn camera2d(&self) -> Camera2D {
+        let (offset, angle) = self.shake();
+        self.camera_at(self.position + offset, angle)
+    }
+
+    // A camera for layers scrolling at `factor` times the world's speed,
+    // keeping the world origin lined up with the screen's top-left.
+    pub fn parallax_camera2d(&self, factor: f32) -> Camera2D {
+        let half = self.view_size() / 2.0;
+        let (offset, angle) = self.shake();
+        let target = half + (self.position + offset - half) * factor;
+        self.camera_at(target, angle)
+    }
+
+    // `visible_rect` in the space of `parallax_camera2d(factor)`.
+    pub fn parallax_visible_rect(&self, factor: f32) -> Rect {
+        let half = self.view_size() / 2.0;
+        self.visible_rect()
+            .offset((self.position - half) * (factor - 1.0))
+    }
+
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        self.view_rect().point() + point / self.config.zoom.max(0.01)
+    }
+
+    fn camera_at(&self, target: Vec2, rotation: f32) -> Camera2D {
+        let zoom = self.config.zoom.max(0.01);
+        Camera2D {
+            target,
+            zoom: vec2(2.0 * zoom / screen_width(), 2.0 * zoom / screen_height()),
+            rotation,
+            ..Default::default()
+        }
+    }
+
+    // Offset in world pixels and angle in degrees for the current trauma.
+    fn shake(&self) -> (Vec2, f32) {
+        if self.trauma <= 0.0 {
+            return (Vec2::ZERO, 0.0);
+        }
+        let c = self.config;
+        let shake = self.trauma * self.trauma;
+        let t = self.time * c.shake_frequency;
+        let offset = vec2(wobble(t, 0.0), wobble(t, 17.0)) * c.shake_max_offset * shake;
+        (offset, wobble(t, 41.0) * c.shake_max_angle * shake)
+    }
+}
+
+// Critically damped spring towards `target` ("SmoothDamp"); `velocity`
+// carries over between calls.
+fn smooth_damp(
+    current: Vec2,
+    target: Vec2,
+    velocity: &mut Vec2,
+    smooth_time: f32,
+    dt: f32,
+) -> Vec2 {
+    let omega = 2.0 / smooth_time;
+    let x = omega * dt;
+    let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * decay;
+    target + (change + temp) * decay
+}
+
+fn clamp_to_world(position: Vec2, view: Vec2, world: Rect) -> Vec2 {
+    let min = world.point() + view / 2.0;
+    let max = (world.point() + world.size() - view / 2.0).max(min);
+    position.clamp(min, max)
+}
+
+// Smooth noise in -1..1; `seed` picks an independent channel.
+fn wobble(t: f32, seed: f32) -> f32 {
+    let sum = (t + seed).sin() * 0.5
+        + (t * 2.3 + seed * 1.7).sin() * 0.3
+        + (t * 4.1 + seed * 3.1).sin() * 0.2;
+    sum.clamp(-1.0, 1.0)
+}
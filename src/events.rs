@@ -0,0 +1,128 @@
+use crate::powerup::PowerUpKind;
+use crate::projectile::Team;
+use crate::scene::{Scene, Sounds};
+use macroquad::audio::{self, PlaySoundParams, Sound};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Something gameplay-relevant that happened during a scene step. The scene
+// queues these (see `Scene::take_events`); audio, HUD and stats react to
+// them instead of the scene calling into those systems. Positions are in
+// world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    // `position` is where the player's feet were.
+    Jumped { position: Vec2 },
+    Fired { position: Vec2, team: Team },
+    // The player landed on one or more enemies and bounced.
+    Stomped { position: Vec2 },
+    // The player lost `amount` health, leaving `health`.
+    Damaged { position: Vec2, amount: u32, health: u32 },
+    Collected { position: Vec2, value: u32, health: u32 },
+    EnemyKilled { position: Vec2, score: u32 },
+    // Ran out of health. The run ends unless a `Respawned` follows.
+    Died { position: Vec2 },
+    CheckpointReached { position: Vec2 },
+    // The player was put back at the respawn point after a fall or death,
+    // losing `score_penalty` points.
+    Respawned { position: Vec2, score_penalty: u32 },
+    // The player reached the level's exit.
+    GoalReached { position: Vec2 },
+    // Follows the `Collected` for a power-up pickup.
+    PowerUpCollected { position: Vec2, kind: PowerUpKind },
+    // Its timer ran out, or its last shield charge was used.
+    PowerUpExpired { kind: PowerUpKind },
+    // A shield charge took a hit in place of the player's health.
+    ShieldAbsorbed { position: Vec2 },
+}
+
+// Plays the sound for each event at the scene's volumes.
+pub fn play_event_sounds(scene: &Scene, events: &[GameEvent], sounds: &Sounds, enabled: bool) {
+    if !enabled {
+        return;
+    }
+    for event in events {
+        match event {
+            GameEvent::Jumped { .. } => play_sound_opt(&sounds.jump, scene.jump_sfx_volume),
+            GameEvent::Stomped { .. }
+            | GameEvent::Damaged { .. }
+            | GameEvent::ShieldAbsorbed { .. } => {
+                play_sound_opt(&sounds.hit, scene.hit_sfx_volume)
+            }
+            GameEvent::Collected { .. } | GameEvent::GoalReached { .. } => {
+                play_sound_opt(&sounds.pickup, scene.pickup_sfx_volume)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn play_sound_opt(sound: &Option<Sound>, volume: f32) {
+    if let Some(s) = sound.as_ref() {
+        audio::play_sound(
+            s,
+            PlaySoundParams {
+                looped: false,
+                volume,
+            },
+        );
+    }
+}
+
+// Running totals over a run's events.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunStats {
+    pub jumps: u32,
+    pub shots: u32,
+    pub stomps: u32,
+    pub hits_taken: u32,
+    pub damage_taken: u32,
+    pub pickups: u32,
+    pub collected: u32,
+    pub enemies_killed: u32,
+    pub score: u32,
+    pub deaths: u32,
+    pub checkpoints: u32,
+    pub respawns: u32,
+    pub goals: u32,
+    pub power_ups: u32,
+    pub hits_absorbed: u32,
+}
+
+impl RunStats {
+    pub fn record(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::Jumped { .. } => self.jumps += 1,
+            GameEvent::Fired { team, .. } => {
+                if team == Team::Player {
+                    self.shots += 1;
+                }
+            }
+            GameEvent::Stomped { .. } => self.stomps += 1,
+            GameEvent::Damaged { amount, .. } => {
+                self.hits_taken += 1;
+                self.damage_taken = self.damage_taken.saturating_add(amount);
+            }
+            GameEvent::Collected { value, .. } => {
+                self.pickups += 1;
+                self.collected = self.collected.saturating_add(value);
+                self.score = self.score.saturating_add(value);
+            }
+            GameEvent::EnemyKilled { score, .. } => {
+                self.enemies_killed += 1;
+                self.score = self.score.saturating_add(score);
+            }
+            GameEvent::Died { .. } => self.deaths += 1,
+            GameEvent::CheckpointReached { .. } => self.checkpoints += 1,
+            GameEvent::Respawned { score_penalty, .. } => {
+                self.respawns += 1;
+                self.score = self.score.saturating_sub(score_penalty);
+            }
+            GameEvent::GoalReached { .. } => self.goals += 1,
+            GameEvent::PowerUpCollected { .. } => self.power_ups += 1,
+            GameEvent::PowerUpExpired { .. } => {}
+            GameEvent::ShieldAbsorbed { .. } => self.hits_absorbed += 1,
+        }
+    }
+}
@@ -1,5 +1,14 @@
-use crate::assets::{Assets, SpriteKind};
-use crate::scene::{Entity, EntityKind, Platform, Scene, InputConfig};
+use crate::animation::Animator;
+use crate::assets::{headless_texture, Assets, SpriteAsset, SpriteKind};
+use crate::background::{BackgroundLayer, BackgroundLayerDef};
+use crate::camera::CameraConfig;
+use crate::lighting::{LightSpec, LightingConfig, LightingOverride};
+use crate::powerup::{PowerUp, PowerUpConfig, PowerUpKind, PowerUpStacking};
+use crate::projectile::ProjectileSpec;
+use crate::scene::{
+    EnemyBehavior, Entity, EntityKind, Hazard, HazardKind, HazardMotion, InputConfig,
+    MovementConfig, Platform, Scene, Tile, TileKind, TileMap, Zone, ZoneKind,
+};
 use macroquad::prelude::*;
 use ::rand::Rng;
 use ::rand::rngs::StdRng;
@@ -26,10 +35,42 @@ pub struct GameRules {
     pub player_start_health: u32,
     pub player_max_health: u32,
     pub enemy_contact_damage: u32,
+    pub enemy_health: u32,
+    pub enemy_death_duration: f32,
+    pub stomp_enabled: bool,
+    pub stomp_bounce_strength: f32,
+    pub stomp_damage: u32,
+    pub stomp_score: u32,
     pub hit_invincibility_duration: f32,
     pub hit_flash_enabled: bool,
     pub player_move_speed: f32,
     pub player_jump_strength: f32,
+    pub player_acceleration: f32,
+    pub player_friction: f32,
+    pub player_air_acceleration: f32,
+    pub player_air_friction: f32,
+    pub player_coyote_time: f32,
+    pub player_jump_buffer: f32,
+    pub player_variable_jump: bool,
+    pub player_jump_cut: f32,
+    pub player_air_jumps: u32,
+    pub player_air_jump_multiplier: f32,
+    pub player_wall_slide_enabled: bool,
+    pub player_wall_slide_speed: f32,
+    pub player_wall_jump_enabled: bool,
+    pub player_wall_jump_strength: f32,
+    pub player_wall_jump_push: f32,
+    pub player_wall_jump_lock: f32,
+    pub player_dash_enabled: bool,
+    pub player_dash_speed: f32,
+    pub player_dash_duration: f32,
+    pub player_dash_cooldown: f32,
+    // Ladders and vines, and swimming; see `MovementConfig`.
+    pub player_climb_speed: f32,
+    pub player_swim_speed_scale: f32,
+    pub player_swim_stroke_strength: f32,
+    pub water_buoyancy: f32,
+    pub water_drag: f32,
     pub gravity: f32,
     pub enemy_speed: f32,
     pub enemy_gravity_scale: f32,
@@ -46,6 +87,69 @@ pub struct GameRules {
     pub platform_min_y: f32,
     pub platform_max_y: f32,
     pub ground_row_enabled: bool,
+    pub platform_solid_chance: f32,
+    // Build the ground row from tiles instead of scattered platforms.
+    pub tilemap_ground_enabled: bool,
+    pub tilemap_tile_size: f32,
+    pub tilemap_ground_depth: usize,
+    pub tilemap_gap_chance: f32,
+    pub tile_hazard_damage: u32,
+    // Chance per platform of a spike strip at one end.
+    pub hazard_spike_chance: f32,
+    // Chance per platform of a saw rolling back and forth along it.
+    pub hazard_saw_chance: f32,
+    pub hazard_saw_speed: f32,
+    // Chance per gap in the tile ground of a lava pool at its bottom.
+    pub hazard_lava_chance: f32,
+    pub hazard_damage: u32,
+    pub hazard_lava_lethal: bool,
+    // Falling out of the world kills instead of teleporting back up.
+    pub hazard_kill_zone_enabled: bool,
+    // Checkpoints placed on platforms, spread out from the player start.
+    pub checkpoint_count: usize,
+    // Deaths per level that respawn the player at the last checkpoint
+    // instead of ending the run.
+    pub death_respawns: u32,
+    // Health after a death respawn; 0 means the starting health.
+    pub respawn_health: u32,
+    pub fall_damage: u32,
+    pub respawn_score_penalty: u32,
+    // Places an exit on the platform farthest from the player start;
+    // touching it completes the level.
+    pub level_goal_enabled: bool,
+    // Chance per level of each power-up kind appearing on a random platform.
+    pub powerup_speed_chance: f32,
+    pub powerup_invincibility_chance: f32,
+    pub powerup_high_jump_chance: f32,
+    pub powerup_magnet_chance: f32,
+    pub powerup_double_damage_chance: f32,
+    pub powerup_shield_chance: f32,
+    // Seconds a power-up lasts. Picking up one that is already active
+    // restarts its timer ("refresh") or adds to it up to
+    // `powerup_max_duration` ("extend").
+    pub powerup_duration: f32,
+    pub powerup_max_duration: f32,
+    pub powerup_stacking: String,
+    pub powerup_speed_multiplier: f32,
+    pub powerup_jump_multiplier: f32,
+    pub powerup_magnet_radius: f32,
+    pub powerup_magnet_speed: f32,
+    pub powerup_damage_multiplier: u32,
+    // Hits a shield pickup absorbs, and the most that can be held.
+    pub powerup_shield_charges: u32,
+    pub powerup_shield_max_charges: u32,
+    // Chance per static one-way platform of a ladder down to whatever is
+    // below it.
+    pub zone_ladder_chance: f32,
+    // Chance per static platform of a vine hanging from its underside.
+    pub zone_vine_chance: f32,
+    pub zone_vine_length: f32,
+    // Chance per gap in the tile ground of it being filled with water.
+    pub zone_water_chance: f32,
+    // Chance per static platform of a low-gravity field above it.
+    pub zone_low_gravity_chance: f32,
+    pub zone_low_gravity_height: f32,
+    pub zone_low_gravity_scale: f32,
     pub enemy_on_platform_chance: f32,
     pub collectible_value: u32,
     pub rare_collectible_chance: f32,
@@ -71,6 +175,14 @@ pub struct GameRules {
     pub key_right_alt: String,
     pub key_jump_primary: String,
     pub key_jump_alt: String,
+    pub key_fire_primary: String,
+    pub key_fire_alt: String,
+    pub key_dash_primary: String,
+    pub key_dash_alt: String,
+    pub key_up_primary: String,
+    pub key_up_alt: String,
+    pub key_down_primary: String,
+    pub key_down_alt: String,
     pub asset_player_size: u32,
     pub asset_enemy_size: u32,
     pub asset_collectible_size: u32,
@@ -120,6 +232,19 @@ pub struct GameRules {
     pub enemy_shoot_range: f32,
     pub projectile_speed: f32,
     pub projectile_damage: u32,
+    pub enemy_projectile_gravity_scale: f32,
+    pub enemy_projectile_lifetime: f32,
+    pub enemy_projectile_sprite: String,
+    pub player_fire_enabled: bool,
+    pub player_fire_interval: f32,
+    pub player_projectile_speed: f32,
+    pub player_projectile_damage: u32,
+    pub player_projectile_gravity_scale: f32,
+    pub player_projectile_lifetime: f32,
+    pub player_projectile_pierce: u32,
+    pub player_projectile_score: u32,
+    pub player_projectile_sprite: String,
+    pub projectiles_collide: bool,
     pub particles_enabled: bool,
     pub jump_particle_count: u32,
     pub hit_particle_count: u32,
@@ -133,9 +258,55 @@ pub struct GameRules {
     pub enemy_chase_range: f32,
     pub enemy_circle_radius: f32,
     pub enemy_circle_speed: f32,
+    pub enemy_flyer_amplitude: f32,
+    pub enemy_flyer_speed: f32,
+    pub enemy_hopper_interval: f32,
+    pub enemy_ambush_range: f32,
+    // Behaviours "mixed" mode picks from, one per enemy.
+    pub enemy_behavior_mix: Vec<String>,
     pub vignette_enabled: bool,
     pub vignette_margin: f32,
     pub vignette_alpha: f32,
+    // Seconds the camera takes to catch up with the player; 0 locks on.
+    pub camera_smooth_time: f32,
+    // Half-size of the box around the view centre the player can move in
+    // without the camera following.
+    pub camera_dead_zone_x: f32,
+    pub camera_dead_zone_y: f32,
+    // The camera leads the player by this many seconds of running, up to
+    // `camera_look_ahead_max` pixels.
+    pub camera_look_ahead: f32,
+    pub camera_look_ahead_max: f32,
+    pub camera_zoom: f32,
+    // Shake on taking a hit: `camera_shake_hit_trauma` (0-1) is added per
+    // hit and drains at `camera_shake_decay` per second; the shake grows
+    // with its square up to the max offset (pixels) and angle (degrees).
+    pub camera_shake_enabled: bool,
+    pub camera_shake_hit_trauma: f32,
+    pub camera_shake_decay: f32,
+    pub camera_shake_max_offset: f32,
+    pub camera_shake_max_angle: f32,
+    pub camera_shake_frequency: f32,
+    // Light map over the world: unlit areas are darkened by
+    // `lighting_darkness` (0-1) and tinted by `lighting_ambient_color`; the
+    // player, collectibles and projectiles carry lights (radius 0 for none)
+    // that flicker by `lighting_flicker` of their radius. lighting.json and
+    // custom levels can override these.
+    pub lighting_enabled: bool,
+    pub lighting_darkness: f32,
+    pub lighting_ambient_color: [f32; 3],
+    pub lighting_player_radius: f32,
+    pub lighting_player_color: [f32; 3],
+    pub lighting_collectible_radius: f32,
+    pub lighting_collectible_color: [f32; 3],
+    pub lighting_projectile_radius: f32,
+    pub lighting_projectile_color: [f32; 3],
+    pub lighting_flicker: f32,
+    pub lighting_flicker_speed: f32,
+    // Save slots on the main menu, and the one Continue and autosaves use.
+    pub save_profile_count: u32,
+    pub save_profile: u32,
+    pub physics_tick_rate: f32,
     pub editor: EditorOptions,
 }
 
@@ -144,7 +315,9 @@ pub struct GameRules {
 pub struct EditorOptions {
     pub default_platform_moving: bool,
     pub default_platform_vertical: bool,
+    pub default_platform_one_way: bool,
     pub default_enemy_jumping: bool,
+    pub default_enemy_behavior: String,
     pub grid_size: f32,
 }
 
@@ -153,7 +326,9 @@ impl Default for EditorOptions {
         Self {
             default_platform_moving: false,
             default_platform_vertical: false,
+            default_platform_one_way: true,
             default_enemy_jumping: false,
+            default_enemy_behavior: "patrol".to_string(),
             grid_size: 64.0,
         }
     }
@@ -178,10 +353,41 @@ impl Default for GameRules {
             player_start_health: 5,
             player_max_health: 3,
             enemy_contact_damage: 1,
+            enemy_health: 1,
+            enemy_death_duration: 0.4,
+            stomp_enabled: true,
+            stomp_bounce_strength: 320.0,
+            stomp_damage: 1,
+            stomp_score: 5,
             hit_invincibility_duration: 0.5,
             hit_flash_enabled: true,
             player_move_speed: 220.0,
             player_jump_strength: 420.0,
+            player_acceleration: 2400.0,
+            player_friction: 2800.0,
+            player_air_acceleration: 1400.0,
+            player_air_friction: 400.0,
+            player_coyote_time: 0.1,
+            player_jump_buffer: 0.1,
+            player_variable_jump: true,
+            player_jump_cut: 0.5,
+            player_air_jumps: 1,
+            player_air_jump_multiplier: 0.85,
+            player_wall_slide_enabled: true,
+            player_wall_slide_speed: 120.0,
+            player_wall_jump_enabled: true,
+            player_wall_jump_strength: 400.0,
+            player_wall_jump_push: 260.0,
+            player_wall_jump_lock: 0.15,
+            player_dash_enabled: true,
+            player_dash_speed: 600.0,
+            player_dash_duration: 0.15,
+            player_dash_cooldown: 0.6,
+            player_climb_speed: 160.0,
+            player_swim_speed_scale: 0.6,
+            player_swim_stroke_strength: 300.0,
+            water_buoyancy: 0.75,
+            water_drag: 3.0,
             gravity: 900.0,
             enemy_speed: 80.0,
             enemy_gravity_scale: 0.0,
@@ -198,6 +404,48 @@ impl Default for GameRules {
             platform_min_y: 0.3,
             platform_max_y: 0.85,
             ground_row_enabled: true,
+            platform_solid_chance: 0.0,
+            tilemap_ground_enabled: false,
+            tilemap_tile_size: 32.0,
+            tilemap_ground_depth: 2,
+            tilemap_gap_chance: 0.08,
+            tile_hazard_damage: 1,
+            hazard_spike_chance: 0.0,
+            hazard_saw_chance: 0.0,
+            hazard_saw_speed: 2.0,
+            hazard_lava_chance: 0.0,
+            hazard_damage: 1,
+            hazard_lava_lethal: true,
+            hazard_kill_zone_enabled: false,
+            checkpoint_count: 0,
+            death_respawns: 0,
+            respawn_health: 0,
+            fall_damage: 0,
+            respawn_score_penalty: 0,
+            level_goal_enabled: false,
+            powerup_speed_chance: 0.0,
+            powerup_invincibility_chance: 0.0,
+            powerup_high_jump_chance: 0.0,
+            powerup_magnet_chance: 0.0,
+            powerup_double_damage_chance: 0.0,
+            powerup_shield_chance: 0.0,
+            powerup_duration: 8.0,
+            powerup_max_duration: 20.0,
+            powerup_stacking: "refresh".to_string(),
+            powerup_speed_multiplier: 1.5,
+            powerup_jump_multiplier: 1.3,
+            powerup_magnet_radius: 160.0,
+            powerup_magnet_speed: 320.0,
+            powerup_damage_multiplier: 2,
+            powerup_shield_charges: 1,
+            powerup_shield_max_charges: 3,
+            zone_ladder_chance: 0.0,
+            zone_vine_chance: 0.0,
+            zone_vine_length: 160.0,
+            zone_water_chance: 0.0,
+            zone_low_gravity_chance: 0.0,
+            zone_low_gravity_height: 320.0,
+            zone_low_gravity_scale: 0.4,
             enemy_on_platform_chance: 0.5,
             collectible_value: 1,
             rare_collectible_chance: 0.1,
@@ -223,6 +471,14 @@ impl Default for GameRules {
             key_right_alt: "Right".to_string(),
             key_jump_primary: "Space".to_string(),
             key_jump_alt: "W".to_string(),
+            key_fire_primary: "J".to_string(),
+            key_fire_alt: "X".to_string(),
+            key_dash_primary: "K".to_string(),
+            key_dash_alt: "Z".to_string(),
+            key_up_primary: "W".to_string(),
+            key_up_alt: "Up".to_string(),
+            key_down_primary: "S".to_string(),
+            key_down_alt: "Down".to_string(),
             asset_player_size: 32,
             asset_enemy_size: 32,
             asset_collectible_size: 20,
@@ -272,6 +528,19 @@ impl Default for GameRules {
             enemy_shoot_range: 320.0,
             projectile_speed: 260.0,
             projectile_damage: 1,
+            enemy_projectile_gravity_scale: 0.0,
+            enemy_projectile_lifetime: 4.0,
+            enemy_projectile_sprite: "projectile_enemy".to_string(),
+            player_fire_enabled: true,
+            player_fire_interval: 0.3,
+            player_projectile_speed: 520.0,
+            player_projectile_damage: 1,
+            player_projectile_gravity_scale: 0.0,
+            player_projectile_lifetime: 1.2,
+            player_projectile_pierce: 0,
+            player_projectile_score: 5,
+            player_projectile_sprite: "projectile_player".to_string(),
+            projectiles_collide: true,
             particles_enabled: true,
             jump_particle_count: 10,
             hit_particle_count: 18,
@@ -285,9 +554,40 @@ impl Default for GameRules {
             enemy_chase_range: 320.0,
             enemy_circle_radius: 40.0,
             enemy_circle_speed: 1.2,
+            enemy_flyer_amplitude: 24.0,
+            enemy_flyer_speed: 2.0,
+            enemy_hopper_interval: 1.5,
+            enemy_ambush_range: 200.0,
+            enemy_behavior_mix: EnemyBehavior::NAMES.iter().map(|n| n.to_string()).collect(),
             vignette_enabled: false,
             vignette_margin: 160.0,
             vignette_alpha: 0.6,
+            camera_smooth_time: 0.12,
+            camera_dead_zone_x: 48.0,
+            camera_dead_zone_y: 32.0,
+            camera_look_ahead: 0.25,
+            camera_look_ahead_max: 96.0,
+            camera_zoom: 1.0,
+            camera_shake_enabled: true,
+            camera_shake_hit_trauma: 0.4,
+            camera_shake_decay: 1.5,
+            camera_shake_max_offset: 12.0,
+            camera_shake_max_angle: 2.0,
+            camera_shake_frequency: 25.0,
+            lighting_enabled: false,
+            lighting_darkness: 0.85,
+            lighting_ambient_color: [1.0, 1.0, 1.0],
+            lighting_player_radius: 220.0,
+            lighting_player_color: [1.0, 0.9, 0.7],
+            lighting_collectible_radius: 80.0,
+            lighting_collectible_color: [1.0, 0.9, 0.4],
+            lighting_projectile_radius: 60.0,
+            lighting_projectile_color: [1.0, 0.6, 0.3],
+            lighting_flicker: 0.08,
+            lighting_flicker_speed: 8.0,
+            save_profile_count: 3,
+            save_profile: 1,
+            physics_tick_rate: 120.0,
             editor: EditorOptions::default(),
         }
     }
@@ -321,6 +621,16 @@ pub fn load_rules(path: &str) -> GameRules {
     rules
 }
 
+// The rules file as written, without `load_rules`' mode adjustments, for
+// changing a single setting in it.
+pub fn read_rules_file(path: &str) -> Result<GameRules, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse rules from {path}: {e}")),
+        Err(_) => Ok(GameRules::default()),
+    }
+}
+
 pub fn save_rules(path: &str, rules: &GameRules) -> Result<(), String> {
     let text =
         serde_json::to_string_pretty(rules).map_err(|e| format!("Failed to serialize rules: {e}"))?;
@@ -422,22 +732,25 @@ pub fn generate_scene(
     let islands_count = rules.layout_islands_count.max(1);
     let islands_span = rules.layout_islands_span.clamp(0.1, 1.0);
 
-    // Effective enemy behavior mode (supports "mixed" as random per level)
-    let enemy_behavior_mode = {
-        let m = rules.enemy_behavior_mode.to_lowercase();
-        if m == "mixed" {
-            let r: f32 = rng.gen();
-            if r < 0.34 {
-                "patrol".to_string()
-            } else if r < 0.67 {
-                "chase".to_string()
+    // Enemy behaviours: one for every enemy, or "mixed" to pick per enemy
+    let enemy_behaviors: Vec<EnemyBehavior> =
+        if rules.enemy_behavior_mode.eq_ignore_ascii_case("mixed") {
+            let mix: Vec<EnemyBehavior> = rules
+                .enemy_behavior_mix
+                .iter()
+                .filter_map(|name| enemy_behavior_from_name(name, rules))
+                .collect();
+            if mix.is_empty() {
+                EnemyBehavior::NAMES
+                    .iter()
+                    .filter_map(|name| enemy_behavior_from_name(name, rules))
+                    .collect()
             } else {
-                "circle".to_string()
+                mix
             }
         } else {
-            rules.enemy_behavior_mode.clone()
-        }
-    };
+            vec![default_enemy_behavior(rules)]
+        };
 
     let mut scene = Scene::new(
         rules.player_move_speed,
@@ -477,21 +790,62 @@ pub fn generate_scene(
         rules.jump_particle_count,
         rules.hit_particle_count,
         rules.pickup_particle_count,
-        enemy_behavior_mode,
-        rules.enemy_chase_range,
-        rules.enemy_circle_radius,
-        rules.enemy_circle_speed,
     );
+    scene.fixed_dt = 1.0 / rules.physics_tick_rate.max(1.0);
+    scene.hazard_tile_damage = rules.tile_hazard_damage;
+    scene.stomp_enabled = rules.stomp_enabled;
+    scene.stomp_bounce_strength = rules.stomp_bounce_strength;
+    scene.stomp_damage = rules.stomp_damage;
+    scene.stomp_score = rules.stomp_score;
+    scene.enemy_death_duration = rules.enemy_death_duration;
+    scene.movement = build_movement_config(rules);
+    scene.effects = assets.particle_effects.for_theme(&rules.theme);
+    scene.respawns_left = rules.death_respawns;
+    scene.respawn_health = match rules.respawn_health {
+        0 => rules.player_start_health,
+        health => health,
+    };
+    scene.fall_damage = rules.fall_damage;
+    scene.respawn_score_penalty = rules.respawn_score_penalty;
+    scene.power_up_config = build_power_up_config(rules);
+    scene.lighting = build_lighting_config(rules);
+    if let Some(over) = assets.lighting.for_theme(&rules.theme) {
+        scene.lighting.apply(over);
+    }
+    scene.player_fire_enabled = rules.player_fire_enabled;
+    scene.player_fire_interval = rules.player_fire_interval.max(0.0);
+    scene.player_shot_score = rules.player_projectile_score;
+    scene.player_shot = ProjectileSpec {
+        texture: projectile_texture(assets, &rules.player_projectile_sprite),
+        source: projectile_source(assets, &rules.player_projectile_sprite),
+        gravity_scale: rules.player_projectile_gravity_scale,
+        lifetime: rules.player_projectile_lifetime,
+        pierce: rules.player_projectile_pierce,
+        collides: rules.projectiles_collide,
+        ..ProjectileSpec::new(rules.player_projectile_speed, rules.player_projectile_damage)
+    };
+    scene.enemy_shot = ProjectileSpec {
+        texture: projectile_texture(assets, &rules.enemy_projectile_sprite),
+        source: projectile_source(assets, &rules.enemy_projectile_sprite),
+        gravity_scale: rules.enemy_projectile_gravity_scale,
+        lifetime: rules.enemy_projectile_lifetime,
+        collides: rules.projectiles_collide,
+        ..ProjectileSpec::new(rules.projectile_speed, rules.projectile_damage)
+    };
 
     // Background
     let backgrounds = assets.sprites_of_kind(SpriteKind::Background);
     if let Some(bg_asset) = choose_random(&backgrounds, rng) {
         scene.background = Some(bg_asset.texture.clone());
+        scene.background_source = bg_asset.source;
     }
+    let layer_defs = assets.backgrounds.for_theme(&rules.theme);
+    scene.background_layers = background_layers(assets, layer_defs);
 
     // Custom level mode: try to load layout from JSON instead of random generation.
     if rules.mode.eq_ignore_ascii_case("custom") {
         if apply_custom_level(&mut scene, assets, rules, level, screen_size) {
+            scene.seed_particles(rng.gen());
             return scene;
         } else {
             eprintln!(
@@ -549,7 +903,7 @@ pub fn generate_scene(
 
             for _ in 0..count {
                 if let Some(sprite) = choose_random(&platform_sprites, rng) {
-                    let tex_w = sprite.texture.width() * rules.sprite_scale;
+                    let tex_w = sprite.size.x * rules.sprite_scale;
                     if tex_w <= 0.0 {
                         continue;
                     }
@@ -591,18 +945,30 @@ pub fn generate_scene(
                     };
 
                     scene.platforms.push(Platform {
-                        texture: sprite.texture.clone(),
-                        position: vec2(x, platform_y),
-                        base_position: vec2(x, platform_y),
                         phase: rng.gen_range(0.0..std::f32::consts::TAU),
                         moving: rules.moving_platform_enabled,
                         vertical: rules.moving_platform_vertical,
+                        one_way: !roll_solid_platform(rules, rng),
+                        name: sprite.name.clone(),
+                        source: sprite.source,
+                        ..Platform::new(sprite.texture.clone(), sprite.size, vec2(x, platform_y))
                     });
                 }
             }
         }
 
-        if rules.ground_row_enabled {
+        if rules.ground_row_enabled && rules.tilemap_ground_enabled {
+            let y = screen_size.y * rules.ground_row_y_factor.clamp(0.0, 2.0);
+            if let Some(sprite) = choose_random(&platform_sprites, rng) {
+                scene.tilemap = Some(build_ground_tilemap(
+                    sprite,
+                    rules,
+                    screen_size.x,
+                    y,
+                    rng,
+                ));
+            }
+        } else if rules.ground_row_enabled {
             let y = screen_size.y * rules.ground_row_y_factor.clamp(0.0, 2.0);
             let max_by_gap = if rules.platform_min_gap_x > 0.0 {
                 (screen_size.x / rules.platform_min_gap_x).max(1.0) as usize
@@ -615,7 +981,7 @@ pub fn generate_scene(
 
             for _ in 0..count {
                 if let Some(sprite) = choose_random(&platform_sprites, rng) {
-                    let tex_w = sprite.texture.width() * rules.sprite_scale;
+                    let tex_w = sprite.size.x * rules.sprite_scale;
                     if tex_w <= 0.0 {
                         continue;
                     }
@@ -623,12 +989,13 @@ pub fn generate_scene(
                     let x = rng.gen_range(margin..(screen_size.x - margin));
 
                     scene.platforms.push(Platform {
-                        texture: sprite.texture.clone(),
-                        position: vec2(x, y),
-                        base_position: vec2(x, y),
                         phase: rng.gen_range(0.0..std::f32::consts::TAU),
                         moving: rules.moving_platform_enabled,
                         vertical: rules.moving_platform_vertical,
+                        one_way: !roll_solid_platform(rules, rng),
+                        name: sprite.name.clone(),
+                        source: sprite.source,
+                        ..Platform::new(sprite.texture.clone(), sprite.size, vec2(x, y))
                     });
                 }
             }
@@ -639,16 +1006,17 @@ pub fn generate_scene(
     let players = assets.sprites_of_kind(SpriteKind::Player);
     if let Some(player_asset) = choose_random(&players, rng) {
         let player_pos = vec2(screen_size.x / 2.0, 0.0);
-        scene.entities.push(Entity {
-            kind: EntityKind::Player,
-            texture: player_asset.texture.clone(),
-            position: player_pos,
-            velocity: Vec2::ZERO,
-            value: 0,
-            health_value: 0,
-            base_position: player_pos,
-            phase: 0.0,
-            jumping: false,
+        scene.respawn_point = player_pos;
+        scene.add_entity(Entity {
+            animator: assets.animation(&player_asset.name).map(Animator::new),
+            source: player_asset.source,
+            name: player_asset.name.clone(),
+            ..Entity::new(
+                EntityKind::Player,
+                player_asset.texture.clone(),
+                player_asset.size,
+                player_pos,
+            )
         });
     }
 
@@ -665,7 +1033,10 @@ pub fn generate_scene(
                 let (x, y) = if use_platform {
                     let idx = rng.gen_range(0..scene.platforms.len());
                     let p = &scene.platforms[idx];
-                    (p.position.x, p.position.y - enemy_asset.texture.height() * rules.sprite_scale)
+                    // Feet on the platform top; bodies aren't pushed out of
+                    // solids they start inside.
+                    let top = p.position.y - p.size.y * rules.sprite_scale / 2.0;
+                    (p.position.x, top - enemy_asset.size.y * rules.sprite_scale / 2.0)
                 } else {
                     let rows = rules.enemy_spawn_rows.max(1);
                     let row = rng.gen_range(0..rows);
@@ -685,17 +1056,26 @@ pub fn generate_scene(
                 };
 
                 let dir = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                let behavior = match enemy_behaviors.len() {
+                    1 => enemy_behaviors[0],
+                    n => enemy_behaviors[rng.gen_range(0..n)],
+                };
 
-                scene.entities.push(Entity {
-                    kind: EntityKind::Enemy,
-                    texture: enemy_asset.texture.clone(),
-                    position: vec2(x, y),
+                scene.add_entity(Entity {
                     velocity: vec2(dir * effective_enemy_speed, 0.0),
-                    value: 0,
-                    health_value: 0,
-                    base_position: vec2(x, y),
                     phase: rng.gen_range(0.0..std::f32::consts::TAU),
                     jumping: rules.enemy_jump_enabled,
+                    behavior,
+                    health: rules.enemy_health.max(1),
+                    animator: assets.animation(&enemy_asset.name).map(Animator::new),
+                    source: enemy_asset.source,
+                    name: enemy_asset.name.clone(),
+                    ..Entity::new(
+                        EntityKind::Enemy,
+                        enemy_asset.texture.clone(),
+                        enemy_asset.size,
+                        vec2(x, y),
+                    )
                 });
             }
         }
@@ -710,9 +1090,158 @@ pub fn generate_scene(
         collectible_multiplier,
     );
 
+    spawn_hazards(&mut scene, assets, rules, rng);
+    spawn_checkpoints(&mut scene, assets, rules);
+    if rules.level_goal_enabled {
+        spawn_level_goal(&mut scene, assets, rules);
+    }
+    spawn_power_ups(&mut scene, assets, rules, rng);
+    spawn_zones(&mut scene, rules, rng);
+    // Drawn last so the layout of existing seeds stays the same
+    scene.seed_particles(rng.gen());
+
     scene
 }
 
+// Puts `checkpoint_count` checkpoints on static platforms, evenly spread by
+// distance from the respawn point so they mark progress away from the start.
+fn spawn_checkpoints(scene: &mut Scene, assets: &Assets, rules: &GameRules) {
+    let sprites = assets.sprites_of_kind(SpriteKind::Checkpoint);
+    let Some(sprite) = sprites.first() else {
+        return;
+    };
+    if rules.checkpoint_count == 0 {
+        return;
+    }
+    let start = scene.respawn_point;
+    let mut candidates: Vec<usize> = (0..scene.platforms.len())
+        .filter(|&i| !scene.platforms[i].moving)
+        .collect();
+    candidates.sort_by(|&a, &b| {
+        let da = scene.platforms[a].position.distance_squared(start);
+        let db = scene.platforms[b].position.distance_squared(start);
+        da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let count = rules.checkpoint_count.min(candidates.len());
+    for n in 1..=count {
+        let platform = &scene.platforms[candidates[n * candidates.len() / (count + 1)]];
+        let top = platform.position.y - platform.size.y * rules.sprite_scale / 2.0;
+        let y = top - sprite.size.y * rules.sprite_scale / 2.0;
+        scene.add_entity(checkpoint_entity(sprite, vec2(platform.position.x, y)));
+    }
+}
+
+// Rolls each power-up kind's chance and puts the ones that come up on
+// random platforms.
+fn spawn_power_ups(scene: &mut Scene, assets: &Assets, rules: &GameRules, rng: &mut impl Rng) {
+    if scene.platforms.is_empty() {
+        return;
+    }
+    for kind in PowerUpKind::ALL {
+        let chance = power_up_chance(rules, kind);
+        if chance <= 0.0 || rng.gen::<f32>() >= chance {
+            continue;
+        }
+        let sprites = power_up_sprites(assets, kind);
+        let Some(sprite) = choose_random(&sprites, rng) else {
+            continue;
+        };
+        let platform = &scene.platforms[rng.gen_range(0..scene.platforms.len())];
+        let y = platform.position.y - sprite.size.y * rules.sprite_scale;
+        let position = vec2(platform.position.x, y);
+        scene.add_entity(Entity {
+            phase: rng.gen_range(0.0..std::f32::consts::TAU),
+            ..power_up_entity(sprite, kind, position)
+        });
+    }
+}
+
+fn power_up_chance(rules: &GameRules, kind: PowerUpKind) -> f32 {
+    match kind {
+        PowerUpKind::Speed => rules.powerup_speed_chance,
+        PowerUpKind::Invincibility => rules.powerup_invincibility_chance,
+        PowerUpKind::HighJump => rules.powerup_high_jump_chance,
+        PowerUpKind::Magnet => rules.powerup_magnet_chance,
+        PowerUpKind::DoubleDamage => rules.powerup_double_damage_chance,
+        PowerUpKind::Shield => rules.powerup_shield_chance,
+    }
+}
+
+// Power-up sprites named after `kind` (e.g. "shield_1"), else any power-up
+// sprite, else the collectible sprites.
+fn power_up_sprites(assets: &Assets, kind: PowerUpKind) -> Vec<&SpriteAsset> {
+    let all = assets.sprites_of_kind(SpriteKind::PowerUp);
+    let named: Vec<&SpriteAsset> = all
+        .iter()
+        .copied()
+        .filter(|s| s.name.to_lowercase().starts_with(kind.name()))
+        .collect();
+    if !named.is_empty() {
+        named
+    } else if !all.is_empty() {
+        all
+    } else {
+        assets.sprites_of_kind(SpriteKind::Collectible)
+    }
+}
+
+// A collectible worth nothing but the power-up, which lasts the scene's
+// default duration.
+fn power_up_entity(sprite: &SpriteAsset, kind: PowerUpKind, position: Vec2) -> Entity {
+    Entity {
+        power_up: Some(PowerUp {
+            kind,
+            duration: 0.0,
+        }),
+        name: sprite.name.clone(),
+        source: sprite.source,
+        ..Entity::new(EntityKind::Collectible, sprite.texture.clone(), sprite.size, position)
+    }
+}
+
+// Stands the level exit on the static platform farthest from the player
+// start.
+fn spawn_level_goal(scene: &mut Scene, assets: &Assets, rules: &GameRules) {
+    let sprites = assets.sprites_of_kind(SpriteKind::GoalCollectible);
+    let Some(sprite) = sprites.first() else {
+        return;
+    };
+    let start = scene.respawn_point;
+    let Some(platform) = scene
+        .platforms
+        .iter()
+        .filter(|p| !p.moving)
+        .max_by(|a, b| {
+            let da = a.position.distance_squared(start);
+            let db = b.position.distance_squared(start);
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    else {
+        return;
+    };
+    let top = platform.position.y - platform.size.y * rules.sprite_scale / 2.0;
+    let y = top - sprite.size.y * rules.sprite_scale / 2.0;
+    let position = vec2(platform.position.x, y);
+    scene.add_entity(level_goal_entity(sprite, position));
+}
+
+fn level_goal_entity(sprite: &SpriteAsset, position: Vec2) -> Entity {
+    Entity {
+        name: sprite.name.clone(),
+        source: sprite.source,
+        ..Entity::new(EntityKind::Goal, sprite.texture.clone(), sprite.size, position)
+    }
+}
+
+fn checkpoint_entity(sprite: &SpriteAsset, position: Vec2) -> Entity {
+    Entity {
+        name: sprite.name.clone(),
+        source: sprite.source,
+        ..Entity::new(EntityKind::Checkpoint, sprite.texture.clone(), sprite.size, position)
+    }
+}
+
 pub fn generate_scene_for_seed(
     assets: &Assets,
     rules: &GameRules,
@@ -788,7 +1317,7 @@ pub fn spawn_collectibles(
                 if let Some(sprite) = choose_random(&collectible_sprites, rng) {
                     let x = platform.position.x;
                     let y =
-                        platform.position.y - sprite.texture.height() * rules.sprite_scale;
+                        platform.position.y - sprite.size.y * rules.sprite_scale;
 
                     let is_rare = rng.gen::<f32>() < rules.rare_collectible_chance;
                     let value = if is_rare {
@@ -802,16 +1331,18 @@ pub fn spawn_collectibles(
                         rules.collectible_health_value
                     };
 
-                    scene.entities.push(Entity {
-                        kind: EntityKind::Collectible,
-                        texture: sprite.texture.clone(),
-                        position: vec2(x, y),
-                        velocity: Vec2::ZERO,
+                    scene.add_entity(Entity {
                         value,
                         health_value,
-                        base_position: vec2(x, y),
                         phase: rng.gen_range(0.0..std::f32::consts::TAU),
-                        jumping: false,
+                        source: sprite.source,
+                        name: sprite.name.clone(),
+                        ..Entity::new(
+                            EntityKind::Collectible,
+                            sprite.texture.clone(),
+                            sprite.size,
+                            vec2(x, y),
+                        )
                     });
                 }
             }
@@ -822,7 +1353,7 @@ pub fn spawn_collectibles(
                 if let Some(sprite) = choose_random(&collectible_sprites, rng) {
                     let x = platform.position.x;
                     let y =
-                        platform.position.y - sprite.texture.height() * rules.sprite_scale;
+                        platform.position.y - sprite.size.y * rules.sprite_scale;
 
                     let is_rare = rng.gen::<f32>() < rules.rare_collectible_chance;
                     let value = if is_rare {
@@ -836,21 +1367,366 @@ pub fn spawn_collectibles(
                         rules.collectible_health_value
                     };
 
-                    scene.entities.push(Entity {
-                        kind: EntityKind::Collectible,
-                        texture: sprite.texture.clone(),
-                        position: vec2(x, y),
-                        velocity: Vec2::ZERO,
+                    scene.add_entity(Entity {
                         value,
                         health_value,
-                        base_position: vec2(x, y),
                         phase: rng.gen_range(0.0..std::f32::consts::TAU),
-                        jumping: false,
+                        source: sprite.source,
+                        name: sprite.name.clone(),
+                        ..Entity::new(
+                            EntityKind::Collectible,
+                            sprite.texture.clone(),
+                            sprite.size,
+                            vec2(x, y),
+                        )
+                    });
+                }
+            }
+        }
+    }
+}
+
+// Spikes and saws on static platforms, lava in ground gaps and a kill zone
+// under the world. Only draws from the rng for hazards with a chance set, so
+// existing seeds keep their layouts.
+fn spawn_hazards(scene: &mut Scene, assets: &Assets, rules: &GameRules, rng: &mut impl Rng) {
+    let scale = rules.sprite_scale;
+    let spikes = hazard_sprites(assets, HazardKind::Spikes);
+    let saws = hazard_sprites(assets, HazardKind::Saw);
+    let lava = hazard_sprites(assets, HazardKind::Lava);
+
+    for i in 0..scene.platforms.len() {
+        let platform = &scene.platforms[i];
+        if platform.moving {
+            continue;
+        }
+        let half_w = platform.size.x * scale / 2.0;
+        let top = platform.position.y - platform.size.y * scale / 2.0;
+        let center_x = platform.position.x;
+
+        if rules.hazard_spike_chance > 0.0 && rng.gen::<f32>() < rules.hazard_spike_chance {
+            if let Some(sprite) = choose_random(&spikes, rng) {
+                let size = sprite.size * scale;
+                let side = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+                let x = center_x + side * (half_w - size.x / 2.0).max(0.0);
+                scene.add_entity(hazard_entity(
+                    rules,
+                    HazardKind::Spikes,
+                    sprite,
+                    vec2(x, top - size.y / 2.0),
+                ));
+            }
+        }
+
+        if rules.hazard_saw_chance > 0.0 && rng.gen::<f32>() < rules.hazard_saw_chance {
+            if let Some(sprite) = choose_random(&saws, rng) {
+                let size = sprite.size * scale;
+                scene.add_entity(Entity {
+                    phase: rng.gen_range(0.0..std::f32::consts::TAU),
+                    hazard: Hazard {
+                        motion: HazardMotion::Line {
+                            dx: (half_w - size.x / 2.0).max(0.0),
+                            dy: 0.0,
+                            speed: rules.hazard_saw_speed,
+                        },
+                        ..hazard_for(rules, HazardKind::Saw)
+                    },
+                    ..hazard_entity(
+                        rules,
+                        HazardKind::Saw,
+                        sprite,
+                        vec2(center_x, top - size.y / 2.0),
+                    )
+                });
+            }
+        }
+    }
+
+    if rules.hazard_lava_chance > 0.0 {
+        let gaps = scene.tilemap.as_ref().map(ground_gaps).unwrap_or_default();
+        for gap in gaps {
+            if rng.gen::<f32>() < rules.hazard_lava_chance {
+                if let Some(sprite) = choose_random(&lava, rng) {
+                    scene.add_entity(Entity {
+                        size: gap.size() / scale,
+                        ..hazard_entity(rules, HazardKind::Lava, sprite, gap.center())
                     });
                 }
             }
         }
     }
+
+    if rules.hazard_kill_zone_enabled {
+        scene.add_entity(kill_zone(rules, scene.world_width, scene.world_height));
+    }
+}
+
+// How far ladders stick up above the platform they lead to, so they can be
+// grabbed from on top of it.
+const LADDER_OVERHANG: f32 = 24.0;
+const LADDER_WIDTH: f32 = 28.0;
+const VINE_WIDTH: f32 = 20.0;
+
+// Ladders down from one-way platforms, vines under platforms, low-gravity
+// fields above them and water in ground gaps. Like hazards, only draws from
+// the rng for zones with a chance set.
+fn spawn_zones(scene: &mut Scene, rules: &GameRules, rng: &mut impl Rng) {
+    let scale = rules.sprite_scale;
+    let mut zones = Vec::new();
+
+    for platform in scene.platforms.iter().filter(|p| !p.moving) {
+        let size = platform.size * scale;
+        let top = platform.position.y - size.y / 2.0;
+        let x = platform.position.x;
+
+        let ladder = rules.zone_ladder_chance > 0.0
+            && rng.gen::<f32>() < rules.zone_ladder_chance
+            && platform.one_way;
+        if ladder {
+            if let Some(foot) = surface_below(scene, x, top) {
+                zones.push(Zone::new(
+                    ZoneKind::Ladder,
+                    Rect::new(
+                        x - LADDER_WIDTH / 2.0,
+                        top - LADDER_OVERHANG,
+                        LADDER_WIDTH,
+                        foot - top + LADDER_OVERHANG,
+                    ),
+                ));
+            }
+        }
+
+        if rules.zone_vine_chance > 0.0 && rng.gen::<f32>() < rules.zone_vine_chance && !ladder {
+            let vine_x = x + rng.gen_range(-0.4..0.4) * size.x;
+            zones.push(Zone::new(
+                ZoneKind::Vine,
+                Rect::new(
+                    vine_x - VINE_WIDTH / 2.0,
+                    top + size.y,
+                    VINE_WIDTH,
+                    rules.zone_vine_length.max(VINE_WIDTH),
+                ),
+            ));
+        }
+
+        if rules.zone_low_gravity_chance > 0.0 && rng.gen::<f32>() < rules.zone_low_gravity_chance {
+            let height = rules.zone_low_gravity_height.max(0.0);
+            zones.push(Zone {
+                gravity_scale: rules.zone_low_gravity_scale.max(0.0),
+                ..Zone::new(
+                    ZoneKind::LowGravity,
+                    Rect::new(x - size.x / 2.0, top - height, size.x, height),
+                )
+            });
+        }
+    }
+
+    if rules.zone_water_chance > 0.0 {
+        if let Some(map) = &scene.tilemap {
+            let map_top = map.bounds().y;
+            for gap in ground_gaps(map) {
+                if rng.gen::<f32>() >= rules.zone_water_chance {
+                    continue;
+                }
+                let pool = Rect::new(gap.x, map_top, gap.w, gap.bottom() - map_top);
+                let has_lava = scene.entities.iter().any(|e| {
+                    matches!(e.kind, EntityKind::Hazard)
+                        && e.hazard.kind == HazardKind::Lava
+                        && pool.contains(e.position)
+                });
+                if !has_lava {
+                    zones.push(Zone::new(ZoneKind::Water, pool));
+                }
+            }
+        }
+    }
+
+    scene.zones.extend(zones);
+}
+
+// Top of the nearest platform or ground tile below `y` at `x`.
+fn surface_below(scene: &Scene, x: f32, y: f32) -> Option<f32> {
+    let scale = scene.sprite_scale;
+    let mut tops: Vec<f32> = scene
+        .platforms
+        .iter()
+        .filter(|p| (x - p.position.x).abs() <= p.size.x * scale / 2.0)
+        .map(|p| p.position.y - p.size.y * scale / 2.0)
+        .collect();
+    if let Some(map) = &scene.tilemap {
+        if let Some((column, _)) = map.cell_at(vec2(x, map.bounds().y)) {
+            for row in 0..map.rows {
+                let kind = map.get(column, row).kind;
+                if matches!(kind, TileKind::Solid | TileKind::OneWay) {
+                    tops.push(map.tile_rect(column, row).y);
+                }
+            }
+        }
+    }
+    tops.into_iter()
+        .filter(|&top| top > y)
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+// Hazard sprites named after `kind` (e.g. "saw_1"), or every hazard sprite
+// when none are.
+fn hazard_sprites(assets: &Assets, kind: HazardKind) -> Vec<&SpriteAsset> {
+    let all = assets.sprites_of_kind(SpriteKind::Hazard);
+    let named: Vec<&SpriteAsset> = all
+        .iter()
+        .copied()
+        .filter(|s| s.name.to_lowercase().starts_with(kind.name()))
+        .collect();
+    if named.is_empty() {
+        all
+    } else {
+        named
+    }
+}
+
+// The rules' damage settings for a hazard of `kind`.
+fn hazard_for(rules: &GameRules, kind: HazardKind) -> Hazard {
+    Hazard {
+        kind,
+        damage: rules.hazard_damage.max(1),
+        lethal: match kind {
+            HazardKind::Lava => rules.hazard_lava_lethal,
+            HazardKind::KillZone => true,
+            _ => false,
+        },
+        motion: HazardMotion::Static,
+    }
+}
+
+fn hazard_entity(rules: &GameRules, kind: HazardKind, sprite: &SpriteAsset, position: Vec2) -> Entity {
+    Entity {
+        name: sprite.name.clone(),
+        source: sprite.source,
+        hazard: hazard_for(rules, kind),
+        ..Entity::new(EntityKind::Hazard, sprite.texture.clone(), sprite.size, position)
+    }
+}
+
+// Extra depth of the kill zone below the teleport line, so fast falls can't
+// skip it.
+const KILL_ZONE_DEPTH: f32 = 200.0;
+
+// An invisible lethal strip under the world, between its bottom edge and
+// the point where falling players would otherwise be teleported back.
+fn kill_zone(rules: &GameRules, world_width: f32, world_height: f32) -> Entity {
+    let depth = rules.player_fall_respawn_offset.max(0.0) + KILL_ZONE_DEPTH;
+    let scale = rules.sprite_scale.max(0.01);
+    Entity {
+        hazard: hazard_for(rules, HazardKind::KillZone),
+        ..Entity::new(
+            EntityKind::Hazard,
+            headless_texture(),
+            vec2(world_width, depth) / scale,
+            vec2(world_width / 2.0, world_height + depth / 2.0),
+        )
+    }
+}
+
+// The bottom row of every run of empty columns in a ground tilemap.
+fn ground_gaps(map: &TileMap) -> Vec<Rect> {
+    let mut gaps = Vec::new();
+    let bottom = map.rows.saturating_sub(1);
+    let mut column = 0;
+    while column < map.columns {
+        if map.get(column, 0).kind != TileKind::Empty {
+            column += 1;
+            continue;
+        }
+        let start = column;
+        while column < map.columns && map.get(column, 0).kind == TileKind::Empty {
+            column += 1;
+        }
+        let first = map.tile_rect(start, bottom);
+        let last = map.tile_rect(column - 1, bottom);
+        gaps.push(Rect::new(first.x, first.y, last.right() - first.x, first.h));
+    }
+    gaps
+}
+
+// A solid strip of ground tiles centred on `y`, spanning the world width,
+// with occasional gaps to jump over. The middle of the map stays solid so
+// the player always has somewhere to land.
+fn build_ground_tilemap(
+    sprite: &SpriteAsset,
+    rules: &GameRules,
+    world_width: f32,
+    y: f32,
+    rng: &mut impl Rng,
+) -> TileMap {
+    let tile_size = rules.tilemap_tile_size.max(4.0);
+    let columns = (world_width / tile_size).ceil().max(1.0) as usize;
+    let depth = rules.tilemap_ground_depth.max(1);
+    let mut map = TileMap::new(vec2(0.0, y - tile_size / 2.0), tile_size, columns, depth);
+    let texture = map.add_texture(sprite.texture.clone(), sprite.source);
+
+    let center = columns / 2;
+    let mut column = 0;
+    while column < columns {
+        if column.abs_diff(center) > 3 && rng.gen::<f32>() < rules.tilemap_gap_chance {
+            column += rng.gen_range(2..=4);
+            continue;
+        }
+        for row in 0..depth {
+            map.set(
+                column,
+                row,
+                Tile {
+                    kind: TileKind::Solid,
+                    texture,
+                },
+            );
+        }
+        column += 1;
+    }
+
+    map
+}
+
+// Behaviour named by `name` (see `EnemyBehavior::NAMES`), with its
+// parameters taken from the rules.
+pub fn enemy_behavior_from_name(name: &str, rules: &GameRules) -> Option<EnemyBehavior> {
+    let behavior = match name.trim().to_lowercase().as_str() {
+        "patrol" => EnemyBehavior::Patrol,
+        "edge_patrol" => EnemyBehavior::EdgePatrol,
+        "chase" => EnemyBehavior::Chase {
+            range: rules.enemy_chase_range,
+        },
+        "circle" => EnemyBehavior::Circle {
+            radius: rules.enemy_circle_radius,
+            speed: rules.enemy_circle_speed,
+        },
+        "flyer" => EnemyBehavior::Flyer {
+            amplitude: rules.enemy_flyer_amplitude,
+            speed: rules.enemy_flyer_speed,
+        },
+        "turret" => EnemyBehavior::Turret,
+        "hopper" => EnemyBehavior::Hopper {
+            range: rules.enemy_chase_range,
+            interval: rules.enemy_hopper_interval,
+        },
+        "ambusher" => EnemyBehavior::Ambusher {
+            range: rules.enemy_ambush_range,
+        },
+        _ => return None,
+    };
+    Some(behavior)
+}
+
+// Behaviour for enemies that don't pick their own: the rules' mode, or
+// patrol for "mixed" and unknown names.
+fn default_enemy_behavior(rules: &GameRules) -> EnemyBehavior {
+    enemy_behavior_from_name(&rules.enemy_behavior_mode, rules).unwrap_or_default()
+}
+
+// Only draws from the rng when solid platforms are enabled, so existing seeds
+// keep generating the same layouts.
+fn roll_solid_platform(rules: &GameRules, rng: &mut impl Rng) -> bool {
+    rules.platform_solid_chance > 0.0 && rng.gen::<f32>() < rules.platform_solid_chance
 }
 
 fn choose_random<'a, T>(items: &'a [&T], rng: &mut impl Rng) -> Option<&'a T> {
@@ -862,6 +1738,22 @@ fn choose_random<'a, T>(items: &'a [&T], rng: &mut impl Rng) -> Option<&'a T> {
     }
 }
 
+// Resolves each layer's sprite, looking in backgrounds/layers first. Layers
+// whose sprite is missing are left out.
+fn background_layers(assets: &Assets, defs: &[BackgroundLayerDef]) -> Vec<BackgroundLayer> {
+    let mut layers = Vec::new();
+    for def in defs {
+        let sprite = assets
+            .sprite_by_kind_and_name(SpriteKind::BackgroundLayer, &def.sprite)
+            .or_else(|| assets.sprite_by_kind_and_name(SpriteKind::Background, &def.sprite));
+        match sprite {
+            Some(sprite) => layers.push(BackgroundLayer::new(def.clone(), sprite)),
+            None => eprintln!("Background layer: no sprite found for '{}'", def.sprite),
+        }
+    }
+    layers
+}
+
 fn level_seed_from_base(base_seed: u64, level: u32) -> u64 {
     const MIX: u64 = 0x9E3779B97F4A7C15;
     base_seed ^ MIX.wrapping_mul(level as u64 + 1)
@@ -878,6 +1770,19 @@ pub struct CustomLevelEntity {
     pub vertical: bool,
     #[serde(default)]
     pub jumping: bool,
+    // Platforms only; solid platforms block from every side.
+    #[serde(default = "default_one_way")]
+    pub one_way: bool,
+    // Enemies only; falls back to the rules' `enemy_behavior_mode`.
+    #[serde(default)]
+    pub behavior: Option<EnemyBehavior>,
+    // Label for looking the entity up at runtime (`Scene::entities_with_tag`).
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+fn default_one_way() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -889,6 +1794,51 @@ pub struct CustomLevelCollectible {
     pub value: u32,
     #[serde(default)]
     pub health: u32,
+    // Makes this a power-up pickup. Its `value` and `health` are used as
+    // given rather than defaulting to the rules'.
+    #[serde(default)]
+    pub power_up: Option<PowerUp>,
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+// Hazards default to the rules' damage settings. `width`/`height` are in
+// world pixels and override the sprite's size; kill zones have no sprite and
+// need both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomLevelHazard {
+    #[serde(default)]
+    pub kind: HazardKind,
+    #[serde(default)]
+    pub sprite: String,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub width: Option<f32>,
+    #[serde(default)]
+    pub height: Option<f32>,
+    #[serde(default)]
+    pub damage: Option<u32>,
+    #[serde(default)]
+    pub lethal: Option<bool>,
+    #[serde(default)]
+    pub motion: HazardMotion,
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+// A ladder, vine, water or low-gravity box centred on `x`/`y`, in world
+// pixels. `gravity_scale` is for low-gravity zones and defaults to the
+// rules' `zone_low_gravity_scale`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomLevelZone {
+    pub kind: ZoneKind,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub gravity_scale: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -897,6 +1847,12 @@ pub struct CustomLevel {
     pub name: Option<String>,
     #[serde(default)]
     pub background: Option<String>,
+    // Replaces the theme's parallax layers; see backgrounds.json.
+    #[serde(default)]
+    pub background_layers: Vec<BackgroundLayerDef>,
+    // Applied over the rules' and theme's lighting.
+    #[serde(default)]
+    pub lighting: Option<LightingOverride>,
     #[serde(default)]
     pub gravity_scale: Option<f32>,
     #[serde(default)]
@@ -909,6 +1865,48 @@ pub struct CustomLevel {
     pub enemies: Vec<CustomLevelEntity>,
     #[serde(default)]
     pub collectibles: Vec<CustomLevelCollectible>,
+    #[serde(default)]
+    pub hazards: Vec<CustomLevelHazard>,
+    #[serde(default)]
+    pub zones: Vec<CustomLevelZone>,
+    // Only `sprite`, `x`, `y` and `tag` are used.
+    #[serde(default)]
+    pub checkpoints: Vec<CustomLevelEntity>,
+    // The level exit; only `sprite`, `x`, `y` and `tag` are used.
+    #[serde(default)]
+    pub goal: Option<CustomLevelEntity>,
+    #[serde(default)]
+    pub tilemap: Option<CustomTileMap>,
+}
+
+// Tile layer for a custom level. Each string in `rows` is one row of cells;
+// every character is looked up in `legend`, and '.' or any symbol missing
+// from the legend is an empty cell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomTileMap {
+    #[serde(default = "default_tile_size")]
+    pub tile_size: f32,
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    #[serde(default)]
+    pub legend: Vec<CustomTileLegend>,
+    #[serde(default)]
+    pub rows: Vec<String>,
+}
+
+fn default_tile_size() -> f32 {
+    32.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomTileLegend {
+    pub symbol: char,
+    pub kind: TileKind,
+    // Platform sprite name used to draw the tile.
+    #[serde(default)]
+    pub sprite: String,
 }
 
 fn apply_custom_level(
@@ -991,6 +1989,10 @@ fn apply_custom_level_def(
             });
         if let Some(s) = sprite {
             scene.background = Some(s.texture.clone());
+            scene.background_source = s.source;
+            // A level picking its own background doesn't get the theme's
+            // layers drawn over it.
+            scene.background_layers.clear();
         } else {
             eprintln!(
                 "Custom level: no background sprite found for '{}'",
@@ -999,6 +2001,13 @@ fn apply_custom_level_def(
         }
     }
 
+    if !def.background_layers.is_empty() {
+        scene.background_layers = background_layers(assets, &def.background_layers);
+    }
+    if let Some(over) = &def.lighting {
+        scene.lighting.apply(over);
+    }
+
     // Optional per-level tuning for gravity / enemy speed
     if let Some(scale) = def.gravity_scale {
         if scale > 0.0 {
@@ -1034,16 +2043,18 @@ fn apply_custom_level_def(
         } else {
             (scene.world_width / 2.0, 0.0)
         };
-        scene.entities.push(Entity {
-            kind: EntityKind::Player,
-            texture: base_sprite.texture.clone(),
-            position: vec2(px, py),
-            velocity: Vec2::ZERO,
-            value: 0,
-            health_value: 0,
-            base_position: vec2(px, py),
-            phase: 0.0,
-            jumping: false,
+        scene.respawn_point = vec2(px, py);
+        scene.add_entity(Entity {
+            animator: assets.animation(&base_sprite.name).map(Animator::new),
+            source: base_sprite.source,
+            name: base_sprite.name.clone(),
+            tag: def.player_start.as_ref().and_then(|start| start.tag.clone()),
+            ..Entity::new(
+                EntityKind::Player,
+                base_sprite.texture.clone(),
+                base_sprite.size,
+                vec2(px, py),
+            )
         });
     } else {
         eprintln!("No player sprites loaded; cannot build custom level.");
@@ -1063,12 +2074,12 @@ fn apply_custom_level_def(
         if let Some(s) = sprite {
             let pos = vec2(p.x, p.y);
             scene.platforms.push(Platform {
-                texture: s.texture.clone(),
-                position: pos,
-                base_position: pos,
-                phase: 0.0,
                 moving: p.moving,
                 vertical: p.vertical,
+                one_way: p.one_way,
+                name: s.name.clone(),
+                source: s.source,
+                ..Platform::new(s.texture.clone(), s.size, pos)
             });
         } else {
             eprintln!(
@@ -1078,6 +2089,11 @@ fn apply_custom_level_def(
         }
     };
 
+    // Tiles
+    if let Some(map_def) = def.tilemap.as_ref() {
+        scene.tilemap = Some(build_custom_tilemap(assets, map_def));
+    }
+
     // Enemies
     for e_def in &def.enemies {
         let sprite = assets
@@ -1088,16 +2104,18 @@ fn apply_custom_level_def(
         if let Some(s) = sprite {
             let pos = vec2(e_def.x, e_def.y);
             let vel = vec2(rules.enemy_speed, 0.0);
-            scene.entities.push(Entity {
-                kind: EntityKind::Enemy,
-                texture: s.texture.clone(),
-                position: pos,
+            scene.add_entity(Entity {
                 velocity: vel,
-                value: 0,
-                health_value: 0,
-                base_position: pos,
-                phase: 0.0,
                 jumping: e_def.jumping,
+                behavior: e_def
+                    .behavior
+                    .unwrap_or_else(|| default_enemy_behavior(rules)),
+                health: rules.enemy_health.max(1),
+                animator: assets.animation(&s.name).map(Animator::new),
+                source: s.source,
+                name: s.name.clone(),
+                tag: e_def.tag.clone(),
+                ..Entity::new(EntityKind::Enemy, s.texture.clone(), s.size, pos)
             });
         } else {
             eprintln!(
@@ -1109,8 +2127,13 @@ fn apply_custom_level_def(
 
     // Collectibles
     for c in &def.collectibles {
-        let sprite = assets
-            .sprite_by_kind_and_name(SpriteKind::Collectible, &c.sprite)
+        let power_up_sprite = c.power_up.and_then(|p| {
+            assets
+                .sprite_by_kind_and_name(SpriteKind::PowerUp, &c.sprite)
+                .or_else(|| power_up_sprites(assets, p.kind).first().copied())
+        });
+        let sprite = power_up_sprite
+            .or_else(|| assets.sprite_by_kind_and_name(SpriteKind::Collectible, &c.sprite))
             .or_else(|| {
                 assets
                     .sprite_by_kind_and_name(SpriteKind::GoalCollectible, &c.sprite)
@@ -1129,27 +2152,25 @@ fn apply_custom_level_def(
             });
         if let Some(s) = sprite {
             let pos = vec2(c.x, c.y);
-            let value = if c.value > 0 {
+            let value = if c.value > 0 || c.power_up.is_some() {
                 c.value
             } else {
                 rules.collectible_value
             };
-            let health_value = if c.health > 0 {
+            let health_value = if c.health > 0 || c.power_up.is_some() {
                 c.health
             } else {
                 rules.collectible_health_value
             };
 
-            scene.entities.push(Entity {
-                kind: EntityKind::Collectible,
-                texture: s.texture.clone(),
-                position: pos,
-                velocity: Vec2::ZERO,
+            scene.add_entity(Entity {
                 value,
                 health_value,
-                base_position: pos,
-                phase: 0.0,
-                jumping: false,
+                power_up: c.power_up,
+                source: s.source,
+                name: s.name.clone(),
+                tag: c.tag.clone(),
+                ..Entity::new(EntityKind::Collectible, s.texture.clone(), s.size, pos)
             });
         } else {
             eprintln!(
@@ -1159,9 +2180,224 @@ fn apply_custom_level_def(
         }
     }
 
+    // Hazards
+    for h in &def.hazards {
+        let mut entity = if h.kind == HazardKind::KillZone {
+            Entity::new(
+                EntityKind::Hazard,
+                headless_texture(),
+                Vec2::ZERO,
+                vec2(h.x, h.y),
+            )
+        } else {
+            let sprite = assets
+                .sprite_by_kind_and_name(SpriteKind::Hazard, &h.sprite)
+                .or_else(|| hazard_sprites(assets, h.kind).first().copied());
+            match sprite {
+                Some(s) => hazard_entity(rules, h.kind, s, vec2(h.x, h.y)),
+                None => {
+                    eprintln!("Custom level: no hazard sprite found for '{}'", h.sprite);
+                    continue;
+                }
+            }
+        };
+        let scale = rules.sprite_scale.max(0.01);
+        if let Some(w) = h.width {
+            entity.size.x = w / scale;
+        }
+        if let Some(hh) = h.height {
+            entity.size.y = hh / scale;
+        }
+        let defaults = hazard_for(rules, h.kind);
+        entity.hazard = Hazard {
+            kind: h.kind,
+            damage: h.damage.unwrap_or(defaults.damage).max(1),
+            lethal: h.lethal.unwrap_or(defaults.lethal),
+            motion: h.motion,
+        };
+        entity.tag = h.tag.clone();
+        scene.add_entity(entity);
+    }
+
+    if rules.hazard_kill_zone_enabled {
+        scene.add_entity(kill_zone(rules, scene.world_width, scene.world_height));
+    }
+
+    // Zones
+    for z in &def.zones {
+        let size = vec2(z.width, z.height).max(Vec2::ZERO);
+        let rect = Rect::new(z.x - size.x / 2.0, z.y - size.y / 2.0, size.x, size.y);
+        let gravity_scale = z.gravity_scale.unwrap_or(rules.zone_low_gravity_scale);
+        scene.zones.push(Zone {
+            gravity_scale: gravity_scale.max(0.0),
+            ..Zone::new(z.kind, rect)
+        });
+    }
+
+    // Checkpoints
+    for c in &def.checkpoints {
+        let sprite = assets
+            .sprite_by_kind_and_name(SpriteKind::Checkpoint, &c.sprite)
+            .or_else(|| assets.sprites_of_kind(SpriteKind::Checkpoint).first().copied());
+        if let Some(s) = sprite {
+            scene.add_entity(Entity {
+                tag: c.tag.clone(),
+                ..checkpoint_entity(s, vec2(c.x, c.y))
+            });
+        } else {
+            eprintln!("Custom level: no checkpoint sprite found for '{}'", c.sprite);
+        }
+    }
+
+    if let Some(g) = &def.goal {
+        let sprite = assets
+            .sprite_by_kind_and_name(SpriteKind::GoalCollectible, &g.sprite)
+            .or_else(|| assets.sprites_of_kind(SpriteKind::GoalCollectible).first().copied());
+        if let Some(s) = sprite {
+            scene.add_entity(Entity {
+                tag: g.tag.clone(),
+                ..level_goal_entity(s, vec2(g.x, g.y))
+            });
+        } else {
+            eprintln!("Custom level: no goal sprite found for '{}'", g.sprite);
+        }
+    }
+
     true
 }
 
+pub fn build_custom_tilemap(assets: &Assets, def: &CustomTileMap) -> TileMap {
+    let columns = def.rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    let mut map = TileMap::new(vec2(def.x, def.y), def.tile_size, columns, def.rows.len());
+
+    let mut symbols: Vec<(char, Tile)> = Vec::new();
+    for entry in &def.legend {
+        let sprite = assets
+            .sprite_by_kind_and_name(SpriteKind::Platform, &entry.sprite)
+            .or_else(|| assets.sprites_of_kind(SpriteKind::Platform).first().copied());
+        match sprite {
+            Some(s) => {
+                let texture = map.add_texture(s.texture.clone(), s.source);
+                symbols.push((
+                    entry.symbol,
+                    Tile {
+                        kind: entry.kind,
+                        texture,
+                    },
+                ));
+            }
+            None => eprintln!(
+                "Custom level: no tile sprite found for '{}'",
+                entry.sprite
+            ),
+        }
+    }
+
+    for (row, line) in def.rows.iter().enumerate() {
+        for (column, symbol) in line.chars().enumerate() {
+            if let Some((_, tile)) = symbols.iter().find(|(s, _)| *s == symbol) {
+                map.set(column, row, *tile);
+            }
+        }
+    }
+
+    map
+}
+
+fn build_power_up_config(rules: &GameRules) -> PowerUpConfig {
+    PowerUpConfig {
+        duration: rules.powerup_duration.max(0.1),
+        max_duration: rules.powerup_max_duration.max(0.1),
+        stacking: match rules.powerup_stacking.to_lowercase().as_str() {
+            "extend" => PowerUpStacking::Extend,
+            _ => PowerUpStacking::Refresh,
+        },
+        speed_multiplier: rules.powerup_speed_multiplier.max(0.0),
+        jump_multiplier: rules.powerup_jump_multiplier.max(0.0),
+        magnet_radius: rules.powerup_magnet_radius.max(0.0),
+        magnet_speed: rules.powerup_magnet_speed.max(0.0),
+        damage_multiplier: rules.powerup_damage_multiplier.max(1),
+        shield_charges: rules.powerup_shield_charges.max(1),
+        shield_max_charges: rules.powerup_shield_max_charges.max(1),
+    }
+}
+
+pub fn build_camera_config(rules: &GameRules) -> CameraConfig {
+    CameraConfig {
+        smooth_time: rules.camera_smooth_time.max(0.0),
+        dead_zone: vec2(rules.camera_dead_zone_x, rules.camera_dead_zone_y).max(Vec2::ZERO),
+        look_ahead: rules.camera_look_ahead.max(0.0),
+        look_ahead_max: rules.camera_look_ahead_max.max(0.0),
+        zoom: rules.camera_zoom.clamp(0.1, 10.0),
+        shake_enabled: rules.camera_shake_enabled,
+        shake_decay: rules.camera_shake_decay.max(0.0),
+        shake_max_offset: rules.camera_shake_max_offset.max(0.0),
+        shake_max_angle: rules.camera_shake_max_angle.max(0.0),
+        shake_frequency: rules.camera_shake_frequency.max(0.0),
+    }
+}
+
+fn build_lighting_config(rules: &GameRules) -> LightingConfig {
+    let color = |[r, g, b]: [f32; 3]| Color::new(r, g, b, 1.0);
+    let spec = |radius: f32, rgb| LightSpec {
+        radius: radius.max(0.0),
+        color: color(rgb),
+    };
+    LightingConfig {
+        enabled: rules.lighting_enabled,
+        darkness: rules.lighting_darkness.clamp(0.0, 1.0),
+        ambient_color: color(rules.lighting_ambient_color),
+        player: spec(rules.lighting_player_radius, rules.lighting_player_color),
+        collectible: spec(rules.lighting_collectible_radius, rules.lighting_collectible_color),
+        projectile: spec(rules.lighting_projectile_radius, rules.lighting_projectile_color),
+        flicker: rules.lighting_flicker.max(0.0),
+        flicker_speed: rules.lighting_flicker_speed.max(0.0),
+    }
+}
+
+fn build_movement_config(rules: &GameRules) -> MovementConfig {
+    MovementConfig {
+        acceleration: rules.player_acceleration.max(0.0),
+        friction: rules.player_friction.max(0.0),
+        air_acceleration: rules.player_air_acceleration.max(0.0),
+        air_friction: rules.player_air_friction.max(0.0),
+        coyote_time: rules.player_coyote_time.max(0.0),
+        jump_buffer: rules.player_jump_buffer.max(0.0),
+        variable_jump: rules.player_variable_jump,
+        jump_cut: rules.player_jump_cut.clamp(0.0, 1.0),
+        air_jumps: rules.player_air_jumps,
+        air_jump_multiplier: rules.player_air_jump_multiplier.max(0.0),
+        wall_slide_enabled: rules.player_wall_slide_enabled,
+        wall_slide_speed: rules.player_wall_slide_speed.max(0.0),
+        wall_jump_enabled: rules.player_wall_jump_enabled,
+        wall_jump_strength: rules.player_wall_jump_strength,
+        wall_jump_push: rules.player_wall_jump_push,
+        wall_jump_lock: rules.player_wall_jump_lock.max(0.0),
+        dash_enabled: rules.player_dash_enabled,
+        dash_speed: rules.player_dash_speed,
+        dash_duration: rules.player_dash_duration.max(0.0),
+        dash_cooldown: rules.player_dash_cooldown.max(0.0),
+        climb_speed: rules.player_climb_speed.max(0.0),
+        swim_speed_scale: rules.player_swim_speed_scale.max(0.0),
+        swim_stroke_strength: rules.player_swim_stroke_strength.max(0.0),
+        water_buoyancy: rules.water_buoyancy,
+        water_drag: rules.water_drag.max(0.0),
+    }
+}
+
+// Projectile sprites are optional; shots fall back to plain squares.
+fn projectile_texture(assets: &Assets, name: &str) -> Option<Texture2D> {
+    assets
+        .sprite_by_kind_and_name(SpriteKind::Projectile, name)
+        .map(|s| s.texture.clone())
+}
+
+fn projectile_source(assets: &Assets, name: &str) -> Option<Rect> {
+    assets
+        .sprite_by_kind_and_name(SpriteKind::Projectile, name)
+        .and_then(|s| s.source)
+}
+
 fn build_input_config(rules: &GameRules) -> InputConfig {
     let scheme = rules.control_scheme.to_lowercase();
 
@@ -1173,6 +2409,14 @@ fn build_input_config(rules: &GameRules) -> InputConfig {
             move_right_alt: None,
             jump_primary: KeyCode::Space,
             jump_alt: Some(KeyCode::W),
+            fire_primary: KeyCode::J,
+            fire_alt: None,
+            dash_primary: KeyCode::K,
+            dash_alt: None,
+            up_primary: KeyCode::W,
+            up_alt: None,
+            down_primary: KeyCode::S,
+            down_alt: None,
         },
         "arrows" => InputConfig {
             move_left_primary: KeyCode::Left,
@@ -1181,6 +2425,14 @@ fn build_input_config(rules: &GameRules) -> InputConfig {
             move_right_alt: None,
             jump_primary: KeyCode::Up,
             jump_alt: None,
+            fire_primary: KeyCode::X,
+            fire_alt: None,
+            dash_primary: KeyCode::Z,
+            dash_alt: None,
+            up_primary: KeyCode::Up,
+            up_alt: None,
+            down_primary: KeyCode::Down,
+            down_alt: None,
         },
         "custom" => InputConfig {
             move_left_primary: parse_key(&rules.key_left_primary).unwrap_or(KeyCode::A),
@@ -1189,6 +2441,14 @@ fn build_input_config(rules: &GameRules) -> InputConfig {
             move_right_alt: parse_optional_key(&rules.key_right_alt),
             jump_primary: parse_key(&rules.key_jump_primary).unwrap_or(KeyCode::Space),
             jump_alt: parse_optional_key(&rules.key_jump_alt),
+            fire_primary: parse_key(&rules.key_fire_primary).unwrap_or(KeyCode::J),
+            fire_alt: parse_optional_key(&rules.key_fire_alt),
+            dash_primary: parse_key(&rules.key_dash_primary).unwrap_or(KeyCode::K),
+            dash_alt: parse_optional_key(&rules.key_dash_alt),
+            up_primary: parse_key(&rules.key_up_primary).unwrap_or(KeyCode::W),
+            up_alt: parse_optional_key(&rules.key_up_alt),
+            down_primary: parse_key(&rules.key_down_primary).unwrap_or(KeyCode::S),
+            down_alt: parse_optional_key(&rules.key_down_alt),
         },
         _ => InputConfig {
             move_left_primary: KeyCode::A,
@@ -1197,6 +2457,14 @@ fn build_input_config(rules: &GameRules) -> InputConfig {
             move_right_alt: Some(KeyCode::Right),
             jump_primary: KeyCode::Space,
             jump_alt: Some(KeyCode::W),
+            fire_primary: KeyCode::J,
+            fire_alt: Some(KeyCode::X),
+            dash_primary: KeyCode::K,
+            dash_alt: Some(KeyCode::Z),
+            up_primary: KeyCode::W,
+            up_alt: Some(KeyCode::Up),
+            down_primary: KeyCode::S,
+            down_alt: Some(KeyCode::Down),
         },
     }
 }
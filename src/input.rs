@@ -0,0 +1,101 @@
+use crate::scene::InputConfig;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// Player actions for a single simulation step. `jump` and `dash` are
+// edge-triggered: they should only be true on the step the button went down.
+// `jump_held` and `fire` are held; releasing jump early cuts the jump short,
+// and the scene's fire interval limits the rate of shots. `up` and `down`
+// are held and climb ladders and vines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputFrame {
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+    pub jump_held: bool,
+    pub fire: bool,
+    pub dash: bool,
+    pub up: bool,
+    pub down: bool,
+}
+
+pub trait InputSource {
+    fn poll(&mut self, config: &InputConfig) -> InputFrame;
+}
+
+// Live keyboard input using the configured bindings. Needs a window.
+pub struct KeyboardInput;
+
+impl InputSource for KeyboardInput {
+    fn poll(&mut self, config: &InputConfig) -> InputFrame {
+        let down = |primary: KeyCode, alt: Option<KeyCode>| {
+            is_key_down(primary) || alt.is_some_and(is_key_down)
+        };
+        let pressed = |primary: KeyCode, alt: Option<KeyCode>| {
+            is_key_pressed(primary) || alt.is_some_and(is_key_pressed)
+        };
+
+        InputFrame {
+            left: down(config.move_left_primary, config.move_left_alt),
+            right: down(config.move_right_primary, config.move_right_alt),
+            jump: pressed(config.jump_primary, config.jump_alt),
+            jump_held: down(config.jump_primary, config.jump_alt),
+            fire: down(config.fire_primary, config.fire_alt),
+            dash: pressed(config.dash_primary, config.dash_alt),
+            up: down(config.up_primary, config.up_alt),
+            down: down(config.down_primary, config.down_alt),
+        }
+    }
+}
+
+// Plays back a fixed list of frames, one per poll. Once the script runs out
+// every further poll returns an idle frame.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedInput {
+    frames: Vec<InputFrame>,
+    cursor: usize,
+}
+
+impl ScriptedInput {
+    pub fn new(frames: Vec<InputFrame>) -> Self {
+        Self { frames, cursor: 0 }
+    }
+
+    // Script file format: a JSON array of frames, e.g.
+    // [{"right": true}, {"right": true, "jump": true}, {}]
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read input script {path}: {e}"))?;
+        let frames: Vec<InputFrame> = serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse input script {path}: {e}"))?;
+        Ok(Self::new(frames))
+    }
+
+    // Append `count` copies of `frame`, handy for building scripts in code.
+    pub fn hold(mut self, frame: InputFrame, count: usize) -> Self {
+        self.frames.extend(std::iter::repeat_n(frame, count));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.frames.len()
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self, _config: &InputConfig) -> InputFrame {
+        let frame = self.frames.get(self.cursor).copied().unwrap_or_default();
+        self.cursor = self.cursor.saturating_add(1);
+        frame
+    }
+}
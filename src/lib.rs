@@ -0,0 +1,18 @@
+pub mod animation;
+pub mod assets;
+pub mod asset_gen;
+pub mod background;
+pub mod camera;
+pub mod events;
+pub mod export;
+pub mod generator;
+pub mod input;
+pub mod lighting;
+pub mod particles;
+pub mod powerup;
+pub mod projectile;
+pub mod registry;
+pub mod save;
+pub mod scene;
+pub mod snapshot;
+pub mod spatial;
@@ -0,0 +1,297 @@
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::camera::Camera;
+
+// A light carried by the player, a collectible or a projectile. A zero
+// radius turns that kind of light off.
+#[derive(Debug, Clone, Copy)]
+pub struct LightSpec {
+    pub radius: f32,
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LightingConfig {
+    pub enabled: bool,
+    // 0 leaves unlit areas as they are, 1 makes them black.
+    pub darkness: f32,
+    // Tint of the light that remains where no light reaches.
+    pub ambient_color: Color,
+    pub player: LightSpec,
+    pub collectible: LightSpec,
+    pub projectile: LightSpec,
+    // Fraction of its radius each light wobbles by, and how fast.
+    pub flicker: f32,
+    pub flicker_speed: f32,
+}
+
+impl Default for LightingConfig {
+    fn default() -> Self {
+        let spec = |radius, color| LightSpec { radius, color };
+        Self {
+            enabled: false,
+            darkness: 0.85,
+            ambient_color: WHITE,
+            player: spec(220.0, Color::new(1.0, 0.9, 0.7, 1.0)),
+            collectible: spec(80.0, Color::new(1.0, 0.9, 0.4, 1.0)),
+            projectile: spec(60.0, Color::new(1.0, 0.6, 0.3, 1.0)),
+            flicker: 0.08,
+            flicker_speed: 8.0,
+        }
+    }
+}
+
+impl LightingConfig {
+    // What the light map is cleared to before lights are added.
+    pub fn ambient(&self) -> Color {
+        let light = 1.0 - self.darkness.clamp(0.0, 1.0);
+        let c = self.ambient_color;
+        Color::new(c.r * light, c.g * light, c.b * light, 1.0)
+    }
+
+    // A light of `spec` at `position`; `seed` keeps neighbouring lights
+    // from flickering in step.
+    pub fn light(&self, spec: LightSpec, position: Vec2, time: f32, seed: f32) -> Light {
+        let t = time * self.flicker_speed + seed;
+        let wobble = (t.sin() * 0.6 + (t * 2.7 + seed).sin() * 0.4) * self.flicker;
+        Light {
+            position,
+            radius: spec.radius * (1.0 + wobble),
+            color: spec.color,
+        }
+    }
+
+    pub fn apply(&mut self, over: &LightingOverride) {
+        if let Some(enabled) = over.enabled {
+            self.enabled = enabled;
+        }
+        if let Some(darkness) = over.darkness {
+            self.darkness = darkness;
+        }
+        if let Some([r, g, b]) = over.ambient_color {
+            self.ambient_color = Color::new(r, g, b, 1.0);
+        }
+        if let Some(flicker) = over.flicker {
+            self.flicker = flicker;
+        }
+        if let Some(radius) = over.player_radius {
+            self.player.radius = radius;
+        }
+        if let Some(radius) = over.collectible_radius {
+            self.collectible.radius = radius;
+        }
+        if let Some(radius) = over.projectile_radius {
+            self.projectile.radius = radius;
+        }
+    }
+}
+
+// Lighting a theme or custom level changes; unset fields keep the rules'
+// values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightingOverride {
+    pub enabled: Option<bool>,
+    pub darkness: Option<f32>,
+    pub ambient_color: Option<[f32; 3]>,
+    pub flicker: Option<f32>,
+    pub player_radius: Option<f32>,
+    pub collectible_radius: Option<f32>,
+    pub projectile_radius: Option<f32>,
+}
+
+// lighting.json overrides the rules' lighting per theme, e.g.
+// {"themes": {"cave": {"enabled": true, "darkness": 0.95}}}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightingFile {
+    pub themes: HashMap<String, LightingOverride>,
+}
+
+impl LightingFile {
+    pub fn for_theme(&self, theme: &str) -> Option<&LightingOverride> {
+        self.themes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(theme))
+            .map(|(_, over)| over)
+    }
+}
+
+// A missing lighting.json just means the rules' lighting everywhere.
+pub fn load_lighting_file(path: &str) -> Result<LightingFile, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return Ok(LightingFile::default()),
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {path}: {e}"))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    pub position: Vec2,
+    pub radius: f32,
+    pub color: Color,
+}
+
+const LIGHT_TEXTURE_SIZE: u16 = 64;
+
+// Macroquad's own sprite shader; the materials only change blending.
+const VERTEX_SHADER: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}"#;
+
+// Screen-sized light map: cleared to the ambient light, lights are added on
+// top, and the result multiplies whatever is already on screen.
+pub struct LightMap {
+    target: RenderTarget,
+    size: (u32, u32),
+    light_texture: Texture2D,
+    additive: Material,
+    multiply: Material,
+}
+
+impl LightMap {
+    // Needs the window, so create it once drawing has started.
+    pub fn new() -> Result<Self, String> {
+        let additive = blend_material(BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::SourceAlpha),
+            BlendFactor::One,
+        ))?;
+        let multiply = blend_material(BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::DestinationColor),
+            BlendFactor::Zero,
+        ))?;
+        let size = screen_size();
+        Ok(Self {
+            target: light_target(size),
+            size,
+            light_texture: radial_gradient(LIGHT_TEXTURE_SIZE),
+            additive,
+            multiply,
+        })
+    }
+
+    // Renders `lights` as seen by `camera` and darkens the screen with the
+    // result. Leaves the default camera set.
+    pub fn draw(&mut self, camera: &Camera, config: &LightingConfig, lights: &[Light]) {
+        let size = screen_size();
+        if size != self.size {
+            self.target = light_target(size);
+            self.size = size;
+        }
+
+        set_camera(&Camera2D {
+            render_target: Some(self.target.clone()),
+            ..camera.camera2d()
+        });
+        clear_background(config.ambient());
+        gl_use_material(&self.additive);
+        let visible = camera.visible_rect();
+        for light in lights {
+            let r = light.radius.max(0.0);
+            let area = Rect::new(light.position.x - r, light.position.y - r, r * 2.0, r * 2.0);
+            if r <= 0.0 || !area.overlaps(&visible) {
+                continue;
+            }
+            draw_texture_ex(
+                &self.light_texture,
+                area.x,
+                area.y,
+                light.color,
+                DrawTextureParams {
+                    dest_size: Some(area.size()),
+                    ..Default::default()
+                },
+            );
+        }
+
+        set_default_camera();
+        gl_use_material(&self.multiply);
+        draw_texture_ex(
+            &self.target.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(screen_width(), screen_height())),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+}
+
+fn screen_size() -> (u32, u32) {
+    (
+        screen_width().max(1.0) as u32,
+        screen_height().max(1.0) as u32,
+    )
+}
+
+fn light_target((w, h): (u32, u32)) -> RenderTarget {
+    let target = render_target(w, h);
+    target.texture.set_filter(FilterMode::Linear);
+    target
+}
+
+fn blend_material(color_blend: BlendState) -> Result<Material, String> {
+    load_material(
+        ShaderSource::Glsl {
+            vertex: VERTEX_SHADER,
+            fragment: FRAGMENT_SHADER,
+        },
+        MaterialParams {
+            pipeline_params: PipelineParams {
+                color_blend: Some(color_blend),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .map_err(|e| format!("Failed to load lighting material: {e:?}"))
+}
+
+// White, fading out from the centre with a soft edge.
+fn radial_gradient(size: u16) -> Texture2D {
+    let half = size as f32 / 2.0;
+    let mut bytes = Vec::with_capacity(size as usize * size as usize * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let d = vec2(x as f32 + 0.5 - half, y as f32 + 0.5 - half).length() / half;
+            let falloff = (1.0 - d).clamp(0.0, 1.0);
+            bytes.extend_from_slice(&[255, 255, 255, (falloff * falloff * 255.0) as u8]);
+        }
+    }
+    let texture = Texture2D::from_rgba8(size, size, &bytes);
+    texture.set_filter(FilterMode::Linear);
+    texture
+}
@@ -1,11 +1,6 @@
-mod assets;
-mod asset_gen;
-mod generator;
-mod scene;
-mod export;
-
-use crate::assets::Assets;
-use crate::export::{
+use engine::assets::{self, Assets};
+use engine::asset_gen;
+use engine::export::{
     export_run_config,
     save_preset,
     load_preset,
@@ -13,8 +8,16 @@ use crate::export::{
     list_run_cartridges,
     load_run_cartridge,
 };
-use crate::generator::{load_rules, save_rules, spawn_collectibles, GameRules};
-use crate::scene::{Scene, Sounds, EntityKind};
+use engine::camera::{Camera, CameraConfig};
+use engine::generator::{
+    build_camera_config, load_rules, read_rules_file, save_rules, spawn_collectibles, GameRules,
+};
+use engine::events::{play_event_sounds, GameEvent, RunStats};
+use engine::input::KeyboardInput;
+use engine::lighting::LightMap;
+use engine::powerup::{PowerUp, PowerUpKind};
+use engine::save::{delete_save, load_save, save_game, SaveGame};
+use engine::scene::{Scene, Sounds, EntityKind, TileMap, ZoneKind};
 use macroquad::prelude::*;
 use ::rand::SeedableRng;
 use ::rand::rngs::StdRng;
@@ -45,6 +48,15 @@ const RESOLUTIONS: &[(f32, f32)] = &[
     (1920.0, 1080.0),
 ];
 
+// Floating "+N" text shown in the world where points were earned.
+struct ScorePopup {
+    position: Vec2,
+    text: String,
+    age: f32,
+}
+
+const POPUP_DURATION: f32 = 0.8;
+
 #[macroquad::main("Random Asset Game")]
 async fn main() {
     let mut rules = load_rules(RULES_PATH);
@@ -84,6 +96,8 @@ async fn main() {
     let mut total_collected: u32 = 0;
     let mut run_score: u32 = 0;
     let mut run_time: f32 = 0.0;
+    let mut run_stats = RunStats::default();
+    let mut popups: Vec<ScorePopup> = Vec::new();
     let mut state = GameState::MainMenu;
     let mut menu_index: i32 = 0;
     let mut pause_index: i32 = 0;
@@ -96,45 +110,132 @@ async fn main() {
     let mut cartridge_index: i32 = 0;
     let mut spawn_rng = StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
     let mut scene = make_scene(&assets, &rules, level, seed);
-    let mut editor_level_data: Option<crate::generator::CustomLevel> = None;
-    let mut editor_tool_index: i32 = 1; // 0: Player, 1: Platform, 2: Enemy, 3: Collectible, 4: Eraser
+    let mut editor_level_data: Option<engine::generator::CustomLevel> = None;
+    // Built from editor_level_data's tiles when drawn; cleared when they change.
+    let mut editor_tilemap: Option<TileMap> = None;
+    let mut editor_tool_index: i32 = 1; // 0: Player, 1: Platform, 2: Enemy, 3: Collectible, 4: Eraser, 5: Tiles
     let mut editor_player_index: i32 = 0;
     let mut editor_platform_index: i32 = 0;
     let mut editor_enemy_index: i32 = 0;
     let mut editor_collectible_index: i32 = 0;
-    let mut editor_camera: Vec2 = Vec2::ZERO;
+    let mut editor_tile_index: i32 = 0;
+    let screen_center = vec2(screen_width(), screen_height()) / 2.0;
+    let mut camera = Camera::new(build_camera_config(&rules), screen_center);
+    let mut editor_camera = Camera::new(CameraConfig::default(), screen_center);
+    // Levels draw unlit if the light map can't be set up.
+    let mut light_map = LightMap::new().map_err(|e| eprintln!("{e}")).ok();
     let mut editor_preview_scale: f32 = 1.0;
     let mut editor_show_assets: bool = false;
     let mut editor_assets_scroll: i32 = 0;
     let mut editor_platform_moving = rules.editor.default_platform_moving;
     let mut editor_platform_vertical = rules.editor.default_platform_vertical;
+    let mut editor_platform_one_way = rules.editor.default_platform_one_way;
     let mut editor_enemy_jumping = rules.editor.default_enemy_jumping;
+    let mut editor_enemy_behavior = rules.editor.default_enemy_behavior.clone();
     let mut editor_last_paint_cell: Option<(i32, i32, i32)> = None;
 
     let mut pregen_handle: Option<std::thread::JoinHandle<Scene>> = None;
     let mut pregen_level: Option<u32> = None;
 
+    // The current profile's save, reread whenever it's written or the
+    // profile changes.
+    let mut profile_save = load_save(save_profile(&rules));
+    if let Err(e) = &profile_save {
+        eprintln!("{e}");
+    }
+    // The player's own rules and seed, put aside while a continued run's
+    // are in play; they come back on returning to the main menu.
+    let mut own_rules: Option<(GameRules, u64)> = None;
+
     loop {
         let frame_start = std::time::Instant::now();
         let dt = get_frame_time();
 
         match state {
             GameState::MainMenu => {
+                if let Some((own, own_seed)) = own_rules.take() {
+                    rules = own;
+                    seed = own_seed;
+                    update_music_volume(&sounds, &rules);
+                }
+
                 let up = is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W);
                 let down = is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S);
 
                 if up {
-                    menu_index = (menu_index - 1).rem_euclid(7);
+                    menu_index = (menu_index - 1).rem_euclid(9);
                 }
                 if down {
-                    menu_index = (menu_index + 1).rem_euclid(7);
+                    menu_index = (menu_index + 1).rem_euclid(9);
+                }
+
+                if menu_index == 2 {
+                    let left = is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A);
+                    let right = is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D);
+                    if left || right {
+                        let count = rules.save_profile_count.max(1) as i32;
+                        let step = if right { 1 } else { -1 };
+                        let current = save_profile(&rules) as i32 - 1;
+                        rules.save_profile = (current + step).rem_euclid(count) as u32 + 1;
+                        // Only the profile is stored; the rules in play may be a
+                        // continued run's.
+                        let stored = read_rules_file(RULES_PATH).and_then(|mut stored| {
+                            stored.save_profile = rules.save_profile;
+                            save_rules(RULES_PATH, &stored)
+                        });
+                        if let Err(e) = stored {
+                            eprintln!("{e}");
+                        }
+                        profile_save = load_save(save_profile(&rules));
+                        if let Err(e) = &profile_save {
+                            eprintln!("{e}");
+                        }
+                    }
                 }
 
                 if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
                     match menu_index {
                         0 => {
+                            // Continue the profile's saved run
+                            if let Ok(Some(save)) = &profile_save {
+                                let profile = rules.save_profile;
+                                let own = std::mem::replace(&mut rules, save.rules.clone());
+                                own_rules = Some((own, seed));
+                                rules.save_profile = profile;
+                                update_music_volume(&sounds, &rules);
+                                seed = save.seed;
+                                rules.seed = Some(seed);
+                                level = save.level;
+                                total_collected = save.total_collected;
+                                run_score = save.run_score;
+                                run_time = save.run_time;
+                                run_stats = save.stats;
+                                spawn_rng =
+                                    StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
+                                pregen_handle = None;
+                                pregen_level = None;
+                                scene = engine::generator::generate_scene_for_seed(
+                                    &assets,
+                                    &rules,
+                                    level,
+                                    Vec2::from(save.world_size),
+                                    seed,
+                                );
+                                if let Some(snapshot) = &save.scene {
+                                    if let Err(e) = scene.restore(snapshot, &assets) {
+                                        eprintln!("{e}");
+                                    }
+                                }
+                                popups.clear();
+                                state = GameState::Playing;
+                            }
+                        }
+                        1 => {
                             level = 1;
                             total_collected = 0;
+                            run_score = 0;
+                            run_stats = RunStats::default();
+                            run_time = 0.0;
                             spawn_rng =
                                 StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
                             pregen_handle = None;
@@ -142,10 +243,13 @@ async fn main() {
                             scene = make_scene(&assets, &rules, level, seed);
                             state = GameState::Playing;
                         }
-                        1 => {
+                        3 => {
                             // Custom Levels: play using custom mode
                             level = 1;
                             total_collected = 0;
+                            run_score = 0;
+                            run_stats = RunStats::default();
+                            run_time = 0.0;
                             rules.mode = "custom".to_string();
                             spawn_rng =
                                 StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
@@ -154,14 +258,15 @@ async fn main() {
                             scene = make_scene(&assets, &rules, level, seed);
                             state = GameState::Playing;
                         }
-                        2 => {
+                        4 => {
                             // Level Editor
                             editor_level = 1;
                             editor_level_data =
-                                crate::generator::load_custom_level(editor_level, &rules);
+                                engine::generator::load_custom_level(editor_level, &rules);
+                            editor_tilemap = None;
                             state = GameState::LevelEditor;
                         }
-                        3 => {
+                        5 => {
                             // Play Cartridge
                             cartridge_files = list_run_cartridges().unwrap_or_else(|e| {
                                 eprintln!("{e}");
@@ -170,14 +275,14 @@ async fn main() {
                             cartridge_index = 0;
                             state = GameState::CartridgeMenu;
                         }
-                        4 => {
+                        6 => {
                             settings_return_to = GameState::MainMenu;
                             state = GameState::Settings;
                         }
-                        5 => {
+                        7 => {
                             state = GameState::Help;
                         }
-                        6 => {
+                        8 => {
                             break;
                         }
                         _ => {}
@@ -216,7 +321,7 @@ async fn main() {
                             };
 
                             pregen_handle = Some(std::thread::spawn(move || {
-                                crate::generator::generate_scene_for_seed(
+                                engine::generator::generate_scene_for_seed(
                                     &assets_clone,
                                     &rules_clone,
                                     target_level,
@@ -233,17 +338,10 @@ async fn main() {
                     {
                         editor_level = level;
                         editor_level_data =
-                            crate::generator::load_custom_level(editor_level, &rules);
-                        if let Some(player_pos) = scene.player_position() {
-                            let sw = screen_width();
-                            let sh = screen_height();
-                            editor_camera = vec2(
-                                player_pos.x - sw / 2.0,
-                                player_pos.y - sh / 2.0,
-                            );
-                        } else {
-                            editor_camera = Vec2::ZERO;
-                        }
+                            engine::generator::load_custom_level(editor_level, &rules);
+                        editor_tilemap = None;
+                        let center = vec2(screen_width(), screen_height()) / 2.0;
+                        editor_camera.snap_to(scene.player_position().unwrap_or(center));
                         state = GameState::LevelEditor;
                     }
 
@@ -251,22 +349,88 @@ async fn main() {
                         scene = make_scene(&assets, &rules, level, seed);
                     }
 
-                    let prev_score = scene.score;
-                    scene.update(dt, &sounds, rules.sfx_enabled);
+                    // Autosave whenever a level starts
+                    if scene.time == 0.0 && scene.accumulator == 0.0 {
+                        let world = [scene.world_width, scene.world_height];
+                        let save = SaveGame {
+                            run_score,
+                            run_time,
+                            total_collected,
+                            stats: run_stats,
+                            ..SaveGame::new(&rules, seed, level, world)
+                        };
+                        profile_save = save_game(save_profile(&rules), &save).map(|_| Some(save));
+                        if let Err(e) = &profile_save {
+                            eprintln!("{e}");
+                        }
+                    }
+
+                    let steps = scene.advance(dt, &mut KeyboardInput);
+                    let events = scene.take_events();
+                    play_event_sounds(&scene, &events, &sounds, rules.sfx_enabled);
+
+                    let mut goal_reached = false;
+                    for event in &events {
+                        run_stats.record(event);
+                        let (position, points) = match *event {
+                            GameEvent::Collected {
+                                position, value, ..
+                            } => {
+                                total_collected = total_collected.saturating_add(value);
+                                (position, value)
+                            }
+                            GameEvent::EnemyKilled { position, score } => (position, score),
+                            GameEvent::GoalReached { .. } => {
+                                goal_reached = true;
+                                continue;
+                            }
+                            GameEvent::Respawned { score_penalty, .. } => {
+                                run_score = run_score.saturating_sub(score_penalty);
+                                continue;
+                            }
+                            GameEvent::Damaged { .. }
+                            | GameEvent::ShieldAbsorbed { .. }
+                            | GameEvent::Died { .. } => {
+                                camera.add_trauma(rules.camera_shake_hit_trauma);
+                                continue;
+                            }
+                            _ => continue,
+                        };
+                        run_score = run_score.saturating_add(points);
+                        if points > 0 {
+                            popups.push(ScorePopup {
+                                position,
+                                text: format!("+{points}"),
+                                age: 0.0,
+                            });
+                        }
+                    }
+                    camera.config = build_camera_config(&rules);
+                    if let Some(target) = scene.player_render_position() {
+                        let world = Rect::new(0.0, 0.0, scene.world_width, scene.world_height);
+                        let velocity = scene.player_velocity().unwrap_or_default();
+                        camera.follow(dt, target, velocity, world);
+                    }
+
+                    for popup in &mut popups {
+                        popup.age += dt;
+                    }
+                    popups.retain(|p| p.age < POPUP_DURATION);
 
                     if scene.player_dead {
                         state = GameState::GameOver;
                     } else {
-                        let gained = scene.score.saturating_sub(prev_score);
-                        total_collected = total_collected.saturating_add(gained as u32);
-                        run_score = run_score.saturating_add(gained as u32);
-                        run_time += dt;
+                        run_time += steps as f32 * scene.fixed_dt;
 
-                        if total_collected >= rules.collectibles_for_level_up {
+                        let level_complete = goal_reached
+                            || total_collected >= rules.collectibles_for_level_up;
+                        if level_complete {
                             if level < rules.max_level {
                                 let new_level = level + 1;
-                                total_collected -= rules.collectibles_for_level_up;
+                                total_collected =
+                                    total_collected.saturating_sub(rules.collectibles_for_level_up);
                                 level = new_level;
+                                popups.clear();
 
                                 if let (Some(pl), Some(handle)) =
                                     (pregen_level.take(), pregen_handle.take())
@@ -292,13 +456,20 @@ async fn main() {
                                     scene = make_scene(&assets, &rules, level, seed);
                                 }
                             } else {
+                                if let Err(e) = delete_save(save_profile(&rules)) {
+                                    eprintln!("{e}");
+                                }
+                                profile_save = Ok(None);
                                 state = GameState::Won;
                             }
                         } else {
                             let has_collectibles = scene
                                 .entities
                                 .iter()
-                                .any(|e| matches!(e.kind, EntityKind::Collectible));
+                                .any(|e| {
+                                    matches!(e.kind, EntityKind::Collectible)
+                                        && e.power_up.is_none()
+                                });
                             if rules.auto_respawn_collectibles && !has_collectibles {
                                 spawn_collectibles(
                                     &mut scene,
@@ -325,6 +496,22 @@ async fn main() {
                 }
 
                 if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
+                    // Leaving the level saves it as it is, to continue later
+                    if pause_index == 2 || pause_index == 3 {
+                        let world = [scene.world_width, scene.world_height];
+                        let save = SaveGame {
+                            run_score,
+                            run_time,
+                            total_collected,
+                            stats: run_stats,
+                            scene: Some(scene.snapshot()),
+                            ..SaveGame::new(&rules, seed, level, world)
+                        };
+                        profile_save = save_game(save_profile(&rules), &save).map(|_| Some(save));
+                        if let Err(e) = &profile_save {
+                            eprintln!("{e}");
+                        }
+                    }
                     match pause_index {
                         0 => state = GameState::Playing,      // Resume
                         1 => {
@@ -392,7 +579,7 @@ async fn main() {
                 }
 
                 if is_key_pressed(KeyCode::Escape) {
-                    if let Err(e) = save_rules(RULES_PATH, &rules) {
+                    if let Err(e) = persist_rules(&rules, &own_rules) {
                         eprintln!("{e}");
                     }
                     state = settings_return_to;
@@ -444,7 +631,7 @@ async fn main() {
                             }
                         }
                         13 => {
-                            if let Err(e) = save_rules(RULES_PATH, &rules) {
+                            if let Err(e) = persist_rules(&rules, &own_rules) {
                                 eprintln!("{e}");
                             }
                             state = settings_return_to;
@@ -465,10 +652,18 @@ async fn main() {
                             3 => rules.key_right_alt = name,
                             4 => rules.key_jump_primary = name,
                             5 => rules.key_jump_alt = name,
+                            6 => rules.key_fire_primary = name,
+                            7 => rules.key_fire_alt = name,
+                            8 => rules.key_dash_primary = name,
+                            9 => rules.key_dash_alt = name,
+                            10 => rules.key_up_primary = name,
+                            11 => rules.key_up_alt = name,
+                            12 => rules.key_down_primary = name,
+                            13 => rules.key_down_alt = name,
                             _ => {}
                         }
                         rebind_step += 1;
-                        if rebind_step >= 6 {
+                        if rebind_step >= 14 {
                             // Rebuild scene so new bindings take effect
                             scene = make_scene(&assets, &rules, level, seed);
                             pregen_handle = None;
@@ -538,7 +733,7 @@ async fn main() {
                 let right = is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D);
 
                 // Fixed list of tunable fields
-                const RULE_ITEMS: usize = 16;
+                const RULE_ITEMS: usize = 28;
 
                 if up {
                     rules_menu_index = (rules_menu_index - 1).rem_euclid(RULE_ITEMS as i32);
@@ -636,42 +831,37 @@ async fn main() {
                             rules.vignette_enabled = !rules.vignette_enabled;
                         }
                         14 => {
-                            // Enemy AI preset
-                            let presets = ["Patrol", "Chase", "Circle", "Mixed"];
-
-                            let mut idx = match rules.enemy_behavior_mode.to_lowercase().as_str() {
-                                "patrol" => 0,
-                                "chase" => 1,
-                                "circle" => 2,
-                                "mixed" => 3,
-                                _ => 0,
+                            // Enemy AI preset: every behaviour, then "mixed"
+                            let names = engine::scene::EnemyBehavior::NAMES;
+                            let preset_count = names.len() as i32 + 1;
+
+                            let mode = rules.enemy_behavior_mode.to_lowercase();
+                            let mut idx = if mode == "mixed" {
+                                names.len()
+                            } else {
+                                names.iter().position(|n| *n == mode).unwrap_or(0)
                             } as i32;
 
-                            idx = (idx + if left { -1 } else { 1 })
-                                .rem_euclid(presets.len() as i32);
+                            idx = (idx + if left { -1 } else { 1 }).rem_euclid(preset_count);
                             let new_idx = idx as usize;
 
-                            match new_idx {
-                                0 => {
-                                    // Patrol
-                                    rules.enemy_behavior_mode = "patrol".to_string();
-                                }
-                                1 => {
-                                    // Chase
+                            match names.get(new_idx).copied() {
+                                Some("chase") => {
                                     rules.enemy_behavior_mode = "chase".to_string();
                                     rules.enemy_chase_range = 480.0;
                                 }
-                                2 => {
-                                    // Circle
+                                Some("circle") => {
                                     rules.enemy_behavior_mode = "circle".to_string();
                                     rules.enemy_circle_radius = 40.0;
                                     rules.enemy_circle_speed = 1.2;
                                 }
-                                3 => {
-                                    // Mixed (random per level)
+                                Some(name) => {
+                                    rules.enemy_behavior_mode = name.to_string();
+                                }
+                                None => {
+                                    // Mixed (random per enemy)
                                     rules.enemy_behavior_mode = "mixed".to_string();
                                 }
-                                _ => {}
                             }
                         }
                         15 => {
@@ -752,12 +942,67 @@ async fn main() {
                                 _ => {}
                             }
                         }
+                        16 => {
+                            // Ground acceleration
+                            rules.player_acceleration =
+                                (rules.player_acceleration + dir * 100.0).max(0.0);
+                        }
+                        17 => {
+                            // Ground friction
+                            rules.player_friction = (rules.player_friction + dir * 100.0).max(0.0);
+                        }
+                        18 => {
+                            // Air acceleration
+                            rules.player_air_acceleration =
+                                (rules.player_air_acceleration + dir * 100.0).max(0.0);
+                        }
+                        19 => {
+                            // Coyote time
+                            rules.player_coyote_time =
+                                (rules.player_coyote_time + dir * 0.02).max(0.0);
+                        }
+                        20 => {
+                            // Jump buffer
+                            rules.player_jump_buffer =
+                                (rules.player_jump_buffer + dir * 0.02).max(0.0);
+                        }
+                        21 => {
+                            // Variable jump height toggle
+                            rules.player_variable_jump = !rules.player_variable_jump;
+                        }
+                        22 => {
+                            // Air jumps
+                            let v = rules.player_air_jumps as i32 + if left { -1 } else { 1 };
+                            rules.player_air_jumps = v.max(0) as u32;
+                        }
+                        23 => {
+                            // Wall slide toggle
+                            rules.player_wall_slide_enabled = !rules.player_wall_slide_enabled;
+                        }
+                        24 => {
+                            // Wall jump toggle
+                            rules.player_wall_jump_enabled = !rules.player_wall_jump_enabled;
+                        }
+                        25 => {
+                            // Dash toggle
+                            rules.player_dash_enabled = !rules.player_dash_enabled;
+                        }
+                        26 => {
+                            // Dash speed
+                            rules.player_dash_speed =
+                                (rules.player_dash_speed + dir * 50.0).max(0.0);
+                        }
+                        27 => {
+                            // Dash cooldown
+                            rules.player_dash_cooldown =
+                                (rules.player_dash_cooldown + dir * 0.05).max(0.0);
+                        }
                         _ => {}
                     }
                 }
 
                 if is_key_pressed(KeyCode::Escape) {
-                    if let Err(e) = save_rules(RULES_PATH, &rules) {
+                    if let Err(e) = persist_rules(&rules, &own_rules) {
                         eprintln!("{e}");
                     }
                     state = GameState::Settings;
@@ -766,15 +1011,22 @@ async fn main() {
             GameState::LevelEditor => {
                 // Ensure we have some data to edit
                 if editor_level_data.is_none() {
-                    editor_level_data = Some(crate::generator::CustomLevel {
+                    editor_level_data = Some(engine::generator::CustomLevel {
                         name: None,
                         background: None,
+                        background_layers: Vec::new(),
+                        lighting: None,
                         gravity_scale: None,
                         enemy_speed_scale: None,
                         player_start: None,
                         platforms: Vec::new(),
                         enemies: Vec::new(),
                         collectibles: Vec::new(),
+                        hazards: Vec::new(),
+                        zones: Vec::new(),
+                        checkpoints: Vec::new(),
+                        goal: None,
+                        tilemap: None,
                     });
                 }
 
@@ -784,31 +1036,37 @@ async fn main() {
                 let right = is_key_down(KeyCode::D) || is_key_down(KeyCode::Right);
 
                 let pan_speed = 400.0;
+                let mut pan = Vec2::ZERO;
                 if left {
-                    editor_camera.x -= pan_speed * dt;
+                    pan.x -= 1.0;
                 }
                 if right {
-                    editor_camera.x += pan_speed * dt;
+                    pan.x += 1.0;
                 }
                 if up {
-                    editor_camera.y -= pan_speed * dt;
+                    pan.y -= 1.0;
                 }
                 if down {
-                    editor_camera.y += pan_speed * dt;
+                    pan.y += 1.0;
                 }
+                editor_camera.pan(pan * pan_speed * dt);
 
                 // Sprite lists for editor tools
-                let player_sprites = assets.sprites_of_kind(crate::assets::SpriteKind::Player);
-                let platform_sprites = assets.sprites_of_kind(crate::assets::SpriteKind::Platform);
-                let enemy_sprites = assets.sprites_of_kind(crate::assets::SpriteKind::Enemy);
+                let player_sprites = assets.sprites_of_kind(engine::assets::SpriteKind::Player);
+                let platform_sprites = assets.sprites_of_kind(engine::assets::SpriteKind::Platform);
+                let enemy_sprites = assets.sprites_of_kind(engine::assets::SpriteKind::Enemy);
                 let collectible_sprites = {
                     let mut list =
-                        assets.sprites_of_kind(crate::assets::SpriteKind::Collectible);
+                        assets.sprites_of_kind(engine::assets::SpriteKind::Collectible);
                     let mut goals =
-                        assets.sprites_of_kind(crate::assets::SpriteKind::GoalCollectible);
+                        assets.sprites_of_kind(engine::assets::SpriteKind::GoalCollectible);
                     list.append(&mut goals);
+                    let mut power_ups =
+                        assets.sprites_of_kind(engine::assets::SpriteKind::PowerUp);
+                    list.append(&mut power_ups);
                     list
                 };
+                let tile_legend = editor_tile_legend(editor_level_data.as_ref(), &platform_sprites);
 
                 // Scroll wheel: either adjust preview scale or scroll asset list
                 let (_wheel_x, wheel_y) = mouse_wheel();
@@ -853,11 +1111,11 @@ async fn main() {
 
                 // Cycle tools with Q/E
                 if is_key_pressed(KeyCode::Q) {
-                    editor_tool_index = (editor_tool_index - 1).rem_euclid(5);
+                    editor_tool_index = (editor_tool_index - 1).rem_euclid(6);
                     editor_assets_scroll = 0;
                 }
                 if is_key_pressed(KeyCode::E) {
-                    editor_tool_index = (editor_tool_index + 1).rem_euclid(5);
+                    editor_tool_index = (editor_tool_index + 1).rem_euclid(6);
                     editor_assets_scroll = 0;
                 }
 
@@ -870,16 +1128,29 @@ async fn main() {
                     editor_platform_vertical = !editor_platform_vertical;
                     rules.editor.default_platform_vertical = editor_platform_vertical;
                 }
+                if is_key_pressed(KeyCode::O) {
+                    editor_platform_one_way = !editor_platform_one_way;
+                    rules.editor.default_platform_one_way = editor_platform_one_way;
+                }
                 if is_key_pressed(KeyCode::J) {
                     editor_enemy_jumping = !editor_enemy_jumping;
                     rules.editor.default_enemy_jumping = editor_enemy_jumping;
                 }
+                if is_key_pressed(KeyCode::B) {
+                    let names = engine::scene::EnemyBehavior::NAMES;
+                    let idx = names
+                        .iter()
+                        .position(|n| *n == editor_enemy_behavior)
+                        .map_or(0, |i| (i + 1) % names.len());
+                    editor_enemy_behavior = names[idx].to_string();
+                    rules.editor.default_enemy_behavior = editor_enemy_behavior.clone();
+                }
 
                 // Change level with Z/X (save current, then load new)
                 if is_key_pressed(KeyCode::Z) && editor_level > 1 {
                     if let Some(ref data) = editor_level_data {
                         if let Err(e) =
-                            crate::generator::save_custom_level(editor_level, &rules, data)
+                            engine::generator::save_custom_level(editor_level, &rules, data)
                         {
                             eprintln!("{e}");
                         }
@@ -887,19 +1158,21 @@ async fn main() {
                     editor_level = editor_level.saturating_sub(1).max(1);
                     editor_assets_scroll = 0;
                     editor_level_data =
-                        crate::generator::load_custom_level(editor_level, &rules);
+                        engine::generator::load_custom_level(editor_level, &rules);
+                    editor_tilemap = None;
                 }
                 if is_key_pressed(KeyCode::X) {
                     if let Some(ref data) = editor_level_data {
                         if let Err(e) =
-                            crate::generator::save_custom_level(editor_level, &rules, data)
+                            engine::generator::save_custom_level(editor_level, &rules, data)
                         {
                             eprintln!("{e}");
                         }
                     }
                     editor_level = editor_level.saturating_add(1);
                     editor_level_data =
-                        crate::generator::load_custom_level(editor_level, &rules);
+                        engine::generator::load_custom_level(editor_level, &rules);
+                    editor_tilemap = None;
                     editor_assets_scroll = 0;
                 }
 
@@ -931,6 +1204,10 @@ async fn main() {
                                         .rem_euclid(collectible_sprites.len() as i32);
                             }
                         }
+                        5 if !tile_legend.is_empty() => {
+                            editor_tile_index =
+                                (editor_tile_index - 1).rem_euclid(tile_legend.len() as i32);
+                        }
                         _ => {}
                     }
                 }
@@ -961,6 +1238,10 @@ async fn main() {
                                         .rem_euclid(collectible_sprites.len() as i32);
                             }
                         }
+                        5 if !tile_legend.is_empty() => {
+                            editor_tile_index =
+                                (editor_tile_index + 1).rem_euclid(tile_legend.len() as i32);
+                        }
                         _ => {}
                     }
                 }
@@ -969,7 +1250,7 @@ async fn main() {
                 if is_key_pressed(KeyCode::S) {
                     if let Some(ref data) = editor_level_data {
                         if let Err(e) =
-                            crate::generator::save_custom_level(editor_level, &rules, data)
+                            engine::generator::save_custom_level(editor_level, &rules, data)
                         {
                             eprintln!("{e}");
                         }
@@ -1006,7 +1287,7 @@ async fn main() {
                         {
                             if let Some(ref data) = editor_level_data {
                                 if let Err(e) =
-                                    crate::generator::save_custom_level(editor_level, &rules, data)
+                                    engine::generator::save_custom_level(editor_level, &rules, data)
                                 {
                                     eprintln!("{e}");
                                 }
@@ -1015,6 +1296,7 @@ async fn main() {
                             level = editor_level;
                             total_collected = 0;
                             run_score = 0;
+                            run_stats = RunStats::default();
                             run_time = 0.0;
                             spawn_rng = StdRng::seed_from_u64(
                                 seed ^ 0x9E3779B97F4A7C15,
@@ -1106,7 +1388,7 @@ async fn main() {
                 if let Some(ref mut data) = editor_level_data {
                     let mouse = mouse_position();
                     let grid = rules.editor.grid_size.max(4.0);
-                    let raw_pos = vec2(mouse.0 + editor_camera.x, mouse.1 + editor_camera.y);
+                    let raw_pos = editor_camera.screen_to_world(vec2(mouse.0, mouse.1));
                     let world_pos = vec2(
                         (raw_pos.x / grid).round() * grid,
                         (raw_pos.y / grid).round() * grid,
@@ -1195,10 +1477,13 @@ async fn main() {
                                         }
                                         editor_platform_moving = p.moving;
                                         editor_platform_vertical = p.vertical;
+                                        editor_platform_one_way = p.one_way;
                                         rules.editor.default_platform_moving =
                                             editor_platform_moving;
                                         rules.editor.default_platform_vertical =
                                             editor_platform_vertical;
+                                        rules.editor.default_platform_one_way =
+                                            editor_platform_one_way;
                                     }
                                 }
                                 2 => {
@@ -1216,6 +1501,11 @@ async fn main() {
                                         editor_enemy_jumping = e.jumping;
                                         rules.editor.default_enemy_jumping =
                                             editor_enemy_jumping;
+                                        if let Some(behavior) = e.behavior {
+                                            editor_enemy_behavior = behavior.name().to_string();
+                                            rules.editor.default_enemy_behavior =
+                                                editor_enemy_behavior.clone();
+                                        }
                                     }
                                 }
                                 3 => {
@@ -1241,6 +1531,34 @@ async fn main() {
                         click_consumed = true;
                     }
 
+                    // Tiles paint on every frame the button is held; setting a
+                    // cell twice is harmless and no cell gets skipped.
+                    if !click_consumed && editor_tool_index == 5 && !tile_legend.is_empty() {
+                        if is_mouse_button_down(MouseButton::Left) {
+                            let entry = &tile_legend
+                                [editor_tile_index.rem_euclid(tile_legend.len() as i32) as usize];
+                            let map = data.tilemap.get_or_insert_with(|| {
+                                engine::generator::CustomTileMap {
+                                    tile_size: grid,
+                                    x: 0.0,
+                                    y: 0.0,
+                                    legend: tile_legend.clone(),
+                                    rows: Vec::new(),
+                                }
+                            });
+                            if set_editor_tile(map, raw_pos, entry.symbol) {
+                                editor_tilemap = None;
+                            }
+                        } else if is_mouse_button_down(MouseButton::Right) {
+                            if let Some(map) = data.tilemap.as_mut() {
+                                if set_editor_tile(map, raw_pos, '.') {
+                                    editor_tilemap = None;
+                                }
+                            }
+                        }
+                        click_consumed = true;
+                    }
+
                     // Continuous paint while dragging, but only when we move to a new grid cell
                     if !click_consumed && is_mouse_button_down(MouseButton::Left) {
                         let cell_x = (world_pos.x / grid).round() as i32;
@@ -1256,13 +1574,16 @@ async fn main() {
                                         player_sprites.len().max(1) as i32,
                                     ) as usize)
                                 {
-                                    data.player_start = Some(crate::generator::CustomLevelEntity {
+                                    data.player_start = Some(engine::generator::CustomLevelEntity {
                                         sprite: sprite.name.clone(),
                                         x: world_pos.x,
                                         y: world_pos.y,
                                         moving: false,
                                         vertical: false,
                                         jumping: false,
+                                        one_way: true,
+                                        behavior: None,
+                                        tag: None,
                                     });
                                 }
                             }
@@ -1274,13 +1595,16 @@ async fn main() {
                                         as usize;
                                     let sprite = platform_sprites[idx];
                                     data.platforms.push(
-                                        crate::generator::CustomLevelEntity {
+                                        engine::generator::CustomLevelEntity {
                                             sprite: sprite.name.clone(),
                                             x: world_pos.x,
                                             y: world_pos.y,
                                             moving: editor_platform_moving,
                                             vertical: editor_platform_vertical,
                                             jumping: false,
+                                            one_way: editor_platform_one_way,
+                                            behavior: None,
+                                            tag: None,
                                         },
                                     );
                                 }
@@ -1293,13 +1617,19 @@ async fn main() {
                                         as usize;
                                     let sprite = enemy_sprites[idx];
                                     data.enemies.push(
-                                        crate::generator::CustomLevelEntity {
+                                        engine::generator::CustomLevelEntity {
                                             sprite: sprite.name.clone(),
                                             x: world_pos.x,
                                             y: world_pos.y,
                                             moving: false,
                                             vertical: false,
                                             jumping: editor_enemy_jumping,
+                                            one_way: true,
+                                            behavior: engine::generator::enemy_behavior_from_name(
+                                                &editor_enemy_behavior,
+                                                &rules,
+                                            ),
+                                            tag: None,
                                         },
                                     );
                                 }
@@ -1311,15 +1641,49 @@ async fn main() {
                                         .rem_euclid(collectible_sprites.len() as i32)
                                         as usize;
                                     let sprite = collectible_sprites[idx];
-                                    data.collectibles.push(
-                                        crate::generator::CustomLevelCollectible {
+                                    // Goal sprites place the level exit; there is only one
+                                    if sprite.kind == engine::assets::SpriteKind::GoalCollectible {
+                                        data.goal = Some(engine::generator::CustomLevelEntity {
                                             sprite: sprite.name.clone(),
                                             x: world_pos.x,
                                             y: world_pos.y,
-                                            value: rules.collectible_value,
-                                            health: rules.collectible_health_value,
-                                        },
-                                    );
+                                            moving: false,
+                                            vertical: false,
+                                            jumping: false,
+                                            one_way: true,
+                                            behavior: None,
+                                            tag: None,
+                                        });
+                                    } else if sprite.kind == engine::assets::SpriteKind::PowerUp {
+                                        let kind = PowerUpKind::from_sprite_name(&sprite.name)
+                                            .unwrap_or(PowerUpKind::Speed);
+                                        data.collectibles.push(
+                                            engine::generator::CustomLevelCollectible {
+                                                sprite: sprite.name.clone(),
+                                                x: world_pos.x,
+                                                y: world_pos.y,
+                                                value: 0,
+                                                health: 0,
+                                                power_up: Some(PowerUp {
+                                                    kind,
+                                                    duration: 0.0,
+                                                }),
+                                                tag: None,
+                                            },
+                                        );
+                                    } else {
+                                        data.collectibles.push(
+                                            engine::generator::CustomLevelCollectible {
+                                                sprite: sprite.name.clone(),
+                                                x: world_pos.x,
+                                                y: world_pos.y,
+                                                value: rules.collectible_value,
+                                                health: rules.collectible_health_value,
+                                                power_up: None,
+                                                tag: None,
+                                            },
+                                        );
+                                    }
                                 }
                             }
                             4 => {
@@ -1336,7 +1700,7 @@ async fn main() {
                         editor_last_paint_cell = None;
                     }
 
-                    if is_mouse_button_pressed(MouseButton::Right) {
+                    if !click_consumed && is_mouse_button_pressed(MouseButton::Right) {
                         // Remove nearest in current category only
                         remove_nearest_in_level_category(
                             data,
@@ -1350,7 +1714,7 @@ async fn main() {
                 if is_key_pressed(KeyCode::Escape) {
                     if let Some(ref data) = editor_level_data {
                         if let Err(e) =
-                            crate::generator::save_custom_level(editor_level, &rules, data)
+                            engine::generator::save_custom_level(editor_level, &rules, data)
                         {
                             eprintln!("{e}");
                         }
@@ -1402,6 +1766,7 @@ async fn main() {
                                 level = 1;
                                 total_collected = 0;
                                 run_score = 0;
+                                run_stats = RunStats::default();
                                 run_time = 0.0;
                                 spawn_rng =
                                     StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
@@ -1436,6 +1801,7 @@ async fn main() {
                     level = 1;
                     total_collected = 0;
                     run_score = 0;
+                    run_stats = RunStats::default();
                     run_time = 0.0;
                     spawn_rng = StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
                     pregen_handle = None;
@@ -1455,6 +1821,7 @@ async fn main() {
                     level = 1;
                     total_collected = 0;
                     run_score = 0;
+                    run_stats = RunStats::default();
                     run_time = 0.0;
                     spawn_rng = StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
                     pregen_handle = None;
@@ -1468,41 +1835,48 @@ async fn main() {
         clear_background(BLACK);
 
         if let GameState::LevelEditor = state {
-            // Simple 2D editor draw with default camera
-            set_default_camera();
+            // Level in world space under the editor camera
+            set_camera(&editor_camera.camera2d());
+            let view = editor_camera.view_rect();
 
             // Draw grid
-            let sw = screen_width();
-            let sh = screen_height();
             let grid = rules.editor.grid_size.max(4.0);
-            let start_x = (editor_camera.x / grid).floor() * grid - editor_camera.x;
-            let start_y = (editor_camera.y / grid).floor() * grid - editor_camera.y;
+            let start_x = (view.x / grid).floor() * grid;
+            let start_y = (view.y / grid).floor() * grid;
 
-            let vertical_lines = (sw / grid).ceil() as i32 + 2;
-            let horizontal_lines = (sh / grid).ceil() as i32 + 2;
+            let vertical_lines = (view.w / grid).ceil() as i32 + 2;
+            let horizontal_lines = (view.h / grid).ceil() as i32 + 2;
 
             for i in 0..vertical_lines {
                 let x = start_x + i as f32 * grid;
-                draw_line(x, 0.0, x, sh, 1.0, Color::new(0.2, 0.2, 0.2, 0.7));
+                draw_line(x, view.y, x, view.bottom(), 1.0, Color::new(0.2, 0.2, 0.2, 0.7));
             }
             for i in 0..horizontal_lines {
                 let y = start_y + i as f32 * grid;
-                draw_line(0.0, y, sw, y, 1.0, Color::new(0.2, 0.2, 0.2, 0.7));
+                draw_line(view.x, y, view.right(), y, 1.0, Color::new(0.2, 0.2, 0.2, 0.7));
             }
 
             // Draw level entities from editor_level_data
             if let Some(ref data) = editor_level_data {
+                // Tiles
+                if let Some(map_def) = data.tilemap.as_ref() {
+                    editor_tilemap
+                        .get_or_insert_with(|| {
+                            engine::generator::build_custom_tilemap(&assets, map_def)
+                        })
+                        .draw(view);
+                }
+
                 // Platforms
                 for p in &data.platforms {
                     if let Some(sprite) = assets.sprite_by_kind_and_name(
-                        crate::assets::SpriteKind::Platform,
+                        engine::assets::SpriteKind::Platform,
                         &p.sprite,
                     ) {
                         let tex = &sprite.texture;
-                        let dest_size =
-                            vec2(tex.width() * rules.sprite_scale, tex.height() * rules.sprite_scale);
-                        let sx = p.x - editor_camera.x - dest_size.x / 2.0;
-                        let sy = p.y - editor_camera.y - dest_size.y / 2.0;
+                        let dest_size = sprite.size * rules.sprite_scale;
+                        let sx = p.x - dest_size.x / 2.0;
+                        let sy = p.y - dest_size.y / 2.0;
                         draw_texture_ex(
                             tex,
                             sx,
@@ -1510,6 +1884,7 @@ async fn main() {
                             WHITE,
                             DrawTextureParams {
                                 dest_size: Some(dest_size),
+                                source: sprite.source,
                                 ..Default::default()
                             },
                         );
@@ -1519,14 +1894,13 @@ async fn main() {
                 // Enemies
                 for e in &data.enemies {
                     if let Some(sprite) = assets.sprite_by_kind_and_name(
-                        crate::assets::SpriteKind::Enemy,
+                        engine::assets::SpriteKind::Enemy,
                         &e.sprite,
                     ) {
                         let tex = &sprite.texture;
-                        let dest_size =
-                            vec2(tex.width() * rules.sprite_scale, tex.height() * rules.sprite_scale);
-                        let sx = e.x - editor_camera.x - dest_size.x / 2.0;
-                        let sy = e.y - editor_camera.y - dest_size.y / 2.0;
+                        let dest_size = sprite.size * rules.sprite_scale;
+                        let sx = e.x - dest_size.x / 2.0;
+                        let sy = e.y - dest_size.y / 2.0;
                         draw_texture_ex(
                             tex,
                             sx,
@@ -1534,6 +1908,7 @@ async fn main() {
                             RED,
                             DrawTextureParams {
                                 dest_size: Some(dest_size),
+                                source: sprite.source,
                                 ..Default::default()
                             },
                         );
@@ -1542,15 +1917,19 @@ async fn main() {
 
                 // Collectibles
                 for c in &data.collectibles {
-                    if let Some(sprite) = assets.sprite_by_kind_and_name(
-                        crate::assets::SpriteKind::Collectible,
-                        &c.sprite,
-                    ) {
+                    if let Some(sprite) = assets
+                        .sprite_by_kind_and_name(engine::assets::SpriteKind::Collectible, &c.sprite)
+                        .or_else(|| {
+                            assets.sprite_by_kind_and_name(
+                                engine::assets::SpriteKind::PowerUp,
+                                &c.sprite,
+                            )
+                        })
+                    {
                         let tex = &sprite.texture;
-                        let dest_size =
-                            vec2(tex.width() * rules.sprite_scale, tex.height() * rules.sprite_scale);
-                        let sx = c.x - editor_camera.x - dest_size.x / 2.0;
-                        let sy = c.y - editor_camera.y - dest_size.y / 2.0;
+                        let dest_size = sprite.size * rules.sprite_scale;
+                        let sx = c.x - dest_size.x / 2.0;
+                        let sy = c.y - dest_size.y / 2.0;
                         draw_texture_ex(
                             tex,
                             sx,
@@ -1558,6 +1937,90 @@ async fn main() {
                             BLUE,
                             DrawTextureParams {
                                 dest_size: Some(dest_size),
+                                source: sprite.source,
+                                ..Default::default()
+                            },
+                        );
+                    }
+                }
+
+                // Hazards (placed by hand in the level JSON; outlined only)
+                for h in &data.hazards {
+                    let sprite_size = assets
+                        .sprite_by_kind_and_name(engine::assets::SpriteKind::Hazard, &h.sprite)
+                        .map(|s| s.size * rules.sprite_scale)
+                        .unwrap_or(vec2(32.0, 32.0));
+                    let w = h.width.unwrap_or(sprite_size.x);
+                    let hh = h.height.unwrap_or(sprite_size.y);
+                    draw_rectangle_lines(
+                        h.x - w / 2.0,
+                        h.y - hh / 2.0,
+                        w,
+                        hh,
+                        2.0,
+                        MAGENTA,
+                    );
+                }
+
+                // Ladders, vines, water and low gravity (JSON only; outlined)
+                for z in &data.zones {
+                    let color = match z.kind {
+                        ZoneKind::Ladder => BROWN,
+                        ZoneKind::Vine => DARKGREEN,
+                        ZoneKind::Water => SKYBLUE,
+                        ZoneKind::LowGravity => VIOLET,
+                    };
+                    draw_rectangle_lines(
+                        z.x - z.width / 2.0,
+                        z.y - z.height / 2.0,
+                        z.width,
+                        z.height,
+                        2.0,
+                        color,
+                    );
+                }
+
+                // Checkpoints
+                for c in &data.checkpoints {
+                    if let Some(sprite) = assets
+                        .sprite_by_kind_and_name(engine::assets::SpriteKind::Checkpoint, &c.sprite)
+                        .or_else(|| {
+                            assets
+                                .sprites_of_kind(engine::assets::SpriteKind::Checkpoint)
+                                .first()
+                                .copied()
+                        })
+                    {
+                        let dest_size = sprite.size * rules.sprite_scale;
+                        draw_texture_ex(
+                            &sprite.texture,
+                            c.x - dest_size.x / 2.0,
+                            c.y - dest_size.y / 2.0,
+                            WHITE,
+                            DrawTextureParams {
+                                dest_size: Some(dest_size),
+                                source: sprite.source,
+                                ..Default::default()
+                            },
+                        );
+                    }
+                }
+
+                // Level exit
+                if let Some(ref g) = data.goal {
+                    if let Some(sprite) = assets.sprite_by_kind_and_name(
+                        engine::assets::SpriteKind::GoalCollectible,
+                        &g.sprite,
+                    ) {
+                        let dest_size = sprite.size * rules.sprite_scale;
+                        draw_texture_ex(
+                            &sprite.texture,
+                            g.x - dest_size.x / 2.0,
+                            g.y - dest_size.y / 2.0,
+                            GREEN,
+                            DrawTextureParams {
+                                dest_size: Some(dest_size),
+                                source: sprite.source,
                                 ..Default::default()
                             },
                         );
@@ -1567,14 +2030,13 @@ async fn main() {
                 // Player start
                 if let Some(ref start) = data.player_start {
                     if let Some(sprite) = assets.sprite_by_kind_and_name(
-                        crate::assets::SpriteKind::Player,
+                        engine::assets::SpriteKind::Player,
                         &start.sprite,
                     ) {
                         let tex = &sprite.texture;
-                        let dest_size =
-                            vec2(tex.width() * rules.sprite_scale, tex.height() * rules.sprite_scale);
-                        let sx = start.x - editor_camera.x - dest_size.x / 2.0;
-                        let sy = start.y - editor_camera.y - dest_size.y / 2.0;
+                        let dest_size = sprite.size * rules.sprite_scale;
+                        let sx = start.x - dest_size.x / 2.0;
+                        let sy = start.y - dest_size.y / 2.0;
                         draw_texture_ex(
                             tex,
                             sx,
@@ -1582,6 +2044,7 @@ async fn main() {
                             GREEN,
                             DrawTextureParams {
                                 dest_size: Some(dest_size),
+                                source: sprite.source,
                                 ..Default::default()
                             },
                         );
@@ -1589,6 +2052,8 @@ async fn main() {
                 }
             }
 
+            set_default_camera();
+
             // HUD for editor
             let tool_name = match editor_tool_index {
                 0 => "Player",
@@ -1596,6 +2061,7 @@ async fn main() {
                 2 => "Enemy",
                 3 => "Collectible",
                 4 => "Eraser",
+                5 => "Tiles",
                 _ => "Unknown",
             };
             let hud = format!(
@@ -1607,13 +2073,28 @@ async fn main() {
             draw_text(hint, 16.0, 64.0, 20.0, GRAY);
 
             let behavior_hint = format!(
-                "Behaviors [M/V/J]: PlatMove={} PlatVert={} EnemyJump={}",
+                "Behaviors [M/V/O/J/B]: PlatMove={} PlatVert={} PlatOneWay={} EnemyJump={} EnemyAI={}",
                 if editor_platform_moving { "On" } else { "Off" },
                 if editor_platform_vertical { "On" } else { "Off" },
-                if editor_enemy_jumping { "On" } else { "Off" }
+                if editor_platform_one_way { "On" } else { "Off" },
+                if editor_enemy_jumping { "On" } else { "Off" },
+                editor_enemy_behavior
             );
             draw_text(&behavior_hint, 16.0, 88.0, 18.0, LIGHTGRAY);
 
+            if editor_tool_index == 5 {
+                let platform_sprites = assets.sprites_of_kind(engine::assets::SpriteKind::Platform);
+                let legend = editor_tile_legend(editor_level_data.as_ref(), &platform_sprites);
+                if !legend.is_empty() {
+                    let entry = &legend[editor_tile_index.rem_euclid(legend.len() as i32) as usize];
+                    let tile_hint = format!(
+                        "Tile [,/.]: '{}' {:?} ({})  | LMB: paint  | RMB: clear",
+                        entry.symbol, entry.kind, entry.sprite
+                    );
+                    draw_text(&tile_hint, 16.0, 112.0, 18.0, LIGHTGRAY);
+                }
+            }
+
             // Assets button
             let btn_x = 16.0;
             let btn_y = 72.0;
@@ -1663,18 +2144,17 @@ async fn main() {
                     _ => GRAY,
                 };
 
-                let draw_preview = |tex: &Texture2D| {
-                    let base_w = tex.width();
-                    let base_h = tex.height();
+                let draw_preview = |sprite: &engine::assets::SpriteAsset| {
                     let scale = rules.sprite_scale * editor_preview_scale;
-                    let dest_size = vec2(base_w * scale, base_h * scale);
+                    let dest_size = sprite.size * scale;
                     draw_texture_ex(
-                        tex,
+                        &sprite.texture,
                         preview_pos.x - dest_size.x / 2.0,
                         preview_pos.y - dest_size.y / 2.0,
                         color,
                         DrawTextureParams {
                             dest_size: Some(dest_size),
+                            source: sprite.source,
                             ..Default::default()
                         },
                     );
@@ -1683,41 +2163,41 @@ async fn main() {
                 match editor_tool_index {
                     0 => {
                         if let Some(sprite) = assets
-                            .sprites_of_kind(crate::assets::SpriteKind::Player)
+                            .sprites_of_kind(engine::assets::SpriteKind::Player)
                             .get(editor_player_index.rem_euclid(
                                 assets
-                                    .sprites_of_kind(crate::assets::SpriteKind::Player)
+                                    .sprites_of_kind(engine::assets::SpriteKind::Player)
                                     .len()
                                     .max(1) as i32,
                             ) as usize)
                         {
-                            draw_preview(&sprite.texture);
+                            draw_preview(sprite);
                         }
                     }
                     1 => {
-                        let list = assets.sprites_of_kind(crate::assets::SpriteKind::Platform);
+                        let list = assets.sprites_of_kind(engine::assets::SpriteKind::Platform);
                         if !list.is_empty() {
                             let idx = editor_platform_index.rem_euclid(list.len() as i32) as usize;
                             let sprite = list[idx];
-                            draw_preview(&sprite.texture);
+                            draw_preview(sprite);
                         }
                     }
                     2 => {
-                        let list = assets.sprites_of_kind(crate::assets::SpriteKind::Enemy);
+                        let list = assets.sprites_of_kind(engine::assets::SpriteKind::Enemy);
                         if !list.is_empty() {
                             let idx = editor_enemy_index.rem_euclid(list.len() as i32) as usize;
                             let sprite = list[idx];
-                            draw_preview(&sprite.texture);
+                            draw_preview(sprite);
                         }
                     }
                     3 => {
                         let list =
-                            assets.sprites_of_kind(crate::assets::SpriteKind::Collectible);
+                            assets.sprites_of_kind(engine::assets::SpriteKind::Collectible);
                         if !list.is_empty() {
                             let idx =
                                 editor_collectible_index.rem_euclid(list.len() as i32) as usize;
                             let sprite = list[idx];
-                            draw_preview(&sprite.texture);
+                            draw_preview(sprite);
                         }
                     }
                     _ => {}
@@ -1758,7 +2238,7 @@ async fn main() {
                 let mut y = panel_y + 40.0;
                 let max_rows = ((panel_h - 48.0) / row_h) as i32;
 
-                use crate::assets::SpriteKind;
+                use engine::assets::SpriteKind;
 
                 let (list, current_index) = match editor_tool_index {
                     0 => (
@@ -1779,6 +2259,8 @@ async fn main() {
                             let mut goals =
                                 assets.sprites_of_kind(SpriteKind::GoalCollectible);
                             list.append(&mut goals);
+                            let mut power_ups = assets.sprites_of_kind(SpriteKind::PowerUp);
+                            list.append(&mut power_ups);
                             list
                         },
                         editor_collectible_index,
@@ -1812,8 +2294,8 @@ async fn main() {
                         let tex = &sprite.texture;
                         let thumb_h = row_h - 6.0;
                         let thumb_w = thumb_h;
-                        let w = tex.width().max(1.0);
-                        let h = tex.height().max(1.0);
+                        let w = sprite.size.x.max(1.0);
+                        let h = sprite.size.y.max(1.0);
                         let scale = (thumb_w / w).min(thumb_h / h);
                         let dest_size = vec2(w * scale, h * scale);
                         draw_texture_ex(
@@ -1823,6 +2305,7 @@ async fn main() {
                             WHITE,
                             DrawTextureParams {
                                 dest_size: Some(dest_size),
+                                source: sprite.source,
                                 ..Default::default()
                             },
                         );
@@ -1842,42 +2325,31 @@ async fn main() {
             }
         } else {
             // Normal game rendering
-            // World camera following the player horizontally and vertically
-            let sw = screen_width();
-            let sh = screen_height();
-            let player_pos = scene
-                .player_position()
-                .unwrap_or(vec2(scene.world_width / 2.0, sh / 2.0));
-            let half_w = sw / 2.0;
-            let half_h = sh / 2.0;
-            let min_cam_x = half_w;
-            let max_cam_x = (scene.world_width - half_w).max(min_cam_x);
-            let min_cam_y = half_h;
-            let world_h = scene.world_height.max(sh);
-            let max_cam_y = (world_h - half_h).max(min_cam_y);
-            let cam_x = player_pos.x.clamp(min_cam_x, max_cam_x);
-            let cam_y = player_pos.y.clamp(min_cam_y, max_cam_y);
-
-            // Parallax background: move slower than the world (0.5x), but keep within bounds
-            let parallax_cam_x = half_w + (cam_x - half_w) * 0.5;
-            let parallax_cam_y = half_h + (cam_y - half_h) * 0.5;
-
-            let parallax_camera = Camera2D {
-                target: vec2(parallax_cam_x, parallax_cam_y),
-                zoom: vec2(2.0 / sw, 2.0 / sh),
-                ..Default::default()
-            };
-            set_camera(&parallax_camera);
-            scene.draw_background();
-
-            // World camera
-            let camera = Camera2D {
-                target: vec2(cam_x, cam_y),
-                zoom: vec2(2.0 / sw, 2.0 / sh),
-                ..Default::default()
-            };
-            set_camera(&camera);
-            scene.draw_world();
+            // Parallax background layers, slower than the world
+            scene.draw_parallax(&camera);
+
+            // World camera following the player
+            set_camera(&camera.camera2d());
+            scene.draw_world(camera.visible_rect());
+            // Light map over the world, under popups and the HUD
+            if scene.lighting.enabled {
+                if let Some(map) = light_map.as_mut() {
+                    map.draw(&camera, &scene.lighting, &scene.lights());
+                    set_camera(&camera.camera2d());
+                }
+            }
+            for popup in &popups {
+                let t = popup.age / POPUP_DURATION;
+                let mut color = YELLOW;
+                color.a = 1.0 - t;
+                draw_text(
+                    &popup.text,
+                    popup.position.x - 10.0,
+                    popup.position.y - 20.0 - t * 30.0,
+                    22.0,
+                    color,
+                );
+            }
             if rules.debug_overlay {
                 scene.debug_draw();
             }
@@ -1905,7 +2377,7 @@ async fn main() {
                 draw_rectangle(sw - margin, margin, margin, sh - 2.0 * margin, color);
             }
 
-            let hud_text = format!(
+            let mut hud_text = format!(
                 "Level: {} | HP: {}/{} | Progress: {}/{}",
                 level,
                 scene.player_health,
@@ -1913,6 +2385,9 @@ async fn main() {
                 total_collected,
                 rules.collectibles_for_level_up
             );
+            if rules.death_respawns > 0 {
+                hud_text.push_str(&format!(" | Respawns: {}", scene.respawns_left));
+            }
 
             let controls_hint = match rules.control_scheme.to_lowercase().as_str() {
                 "wasd" => "Controls: A/D move, Space/W jump, R to regenerate",
@@ -1936,6 +2411,22 @@ async fn main() {
                 YELLOW,
             );
 
+            // Active power-ups, each with a bar for the time left
+            let mut power_up_y = 64.0;
+            for active in scene.power_ups.active() {
+                let color = active.kind.color();
+                let label = if active.kind == PowerUpKind::Shield {
+                    format!("{} x{}", active.kind.label(), active.charges)
+                } else {
+                    active.kind.label().to_string()
+                };
+                draw_text(&label, 16.0, power_up_y + 14.0, 20.0, color);
+                let fill = (active.remaining / active.duration.max(0.001)).clamp(0.0, 1.0);
+                draw_rectangle(160.0, power_up_y + 3.0, 120.0 * fill, 12.0, color);
+                draw_rectangle_lines(160.0, power_up_y + 3.0, 120.0, 12.0, 1.0, color);
+                power_up_y += 20.0;
+            }
+
             // Boss / event level label
             let is_boss_level = rules.boss_level_interval > 0
                 && level > 0
@@ -1982,13 +2473,30 @@ async fn main() {
         match state {
             GameState::MainMenu => {
                 let title = "Random Platformer Engine";
-                let opt1 = "Start Game";
-                let opt2 = "Custom Levels";
-                let opt3 = "Level Editor";
-                let opt4 = "Play Cartridge";
-                let opt5 = "Settings";
-                let opt6 = "Help";
-                let opt7 = "Quit";
+                let continue_label = match &profile_save {
+                    Ok(Some(save)) => {
+                        format!("Continue - Level {}, score {}", save.level, save.run_score)
+                    }
+                    Ok(None) => "Continue (no save)".to_string(),
+                    Err(_) => "Continue (save damaged)".to_string(),
+                };
+                let profile_label = format!(
+                    "< Profile {}/{} >",
+                    save_profile(&rules),
+                    rules.save_profile_count.max(1)
+                );
+                let options = [
+                    continue_label.as_str(),
+                    "Start Game",
+                    profile_label.as_str(),
+                    "Custom Levels",
+                    "Level Editor",
+                    "Play Cartridge",
+                    "Settings",
+                    "Help",
+                    "Quit",
+                ];
+                let can_continue = matches!(profile_save, Ok(Some(_)));
 
                 let center_x = screen_width() * 0.5;
                 let center_y = screen_height() * 0.5;
@@ -1996,68 +2504,27 @@ async fn main() {
                 draw_text(
                     title,
                     center_x - 220.0,
-                    center_y - 80.0,
+                    center_y - 110.0,
                     36.0,
                     YELLOW,
                 );
 
-                let color1 = if menu_index == 0 { GREEN } else { GRAY };
-                let color2 = if menu_index == 1 { GREEN } else { GRAY };
-                let color3 = if menu_index == 2 { GREEN } else { GRAY };
-                let color4 = if menu_index == 3 { GREEN } else { GRAY };
-                let color5 = if menu_index == 4 { GREEN } else { GRAY };
-                let color6 = if menu_index == 5 { GREEN } else { GRAY };
-                let color7 = if menu_index == 6 { GREEN } else { GRAY };
-
-                draw_text(
-                    opt1,
-                    center_x - 80.0,
-                    center_y,
-                    28.0,
-                    color1,
-                );
-                draw_text(
-                    opt2,
-                    center_x - 80.0,
-                    center_y + 40.0,
-                    28.0,
-                    color2,
-                );
-                draw_text(
-                    opt3,
-                    center_x - 80.0,
-                    center_y + 80.0,
-                    28.0,
-                    color3,
-                );
-                draw_text(
-                    opt4,
-                    center_x - 80.0,
-                    center_y + 120.0,
-                    28.0,
-                    color4,
-                );
-                draw_text(
-                    opt5,
-                    center_x - 80.0,
-                    center_y + 160.0,
-                    28.0,
-                    color5,
-                );
-                draw_text(
-                    opt6,
-                    center_x - 80.0,
-                    center_y + 200.0,
-                    28.0,
-                    color6,
-                );
-                draw_text(
-                    opt7,
-                    center_x - 80.0,
-                    center_y + 240.0,
-                    28.0,
-                    color7,
-                );
+                for (i, label) in options.iter().enumerate() {
+                    let color = if menu_index == i as i32 {
+                        GREEN
+                    } else if i == 0 && !can_continue {
+                        DARKGRAY
+                    } else {
+                        GRAY
+                    };
+                    draw_text(
+                        label,
+                        center_x - 80.0,
+                        center_y - 50.0 + i as f32 * 34.0,
+                        28.0,
+                        color,
+                    );
+                }
             }
             GameState::Paused => {
                 let title = "Paused";
@@ -2157,8 +2624,13 @@ async fn main() {
                 // Derive human-friendly AI preset name from current rules
                 let ai_preset_name = match rules.enemy_behavior_mode.to_lowercase().as_str() {
                     "patrol" => "Patrol",
+                    "edge_patrol" => "Edge Patrol",
                     "chase" => "Chase",
                     "circle" => "Circle",
+                    "flyer" => "Flyer",
+                    "turret" => "Turret",
+                    "hopper" => "Hopper",
+                    "ambusher" => "Ambusher",
                     "mixed" => "Mixed",
                     _ => "Custom",
                 };
@@ -2215,16 +2687,55 @@ async fn main() {
                         if rules.enemy_shoot_enabled { "On" } else { "Off" }
                     ),
                     format!("Editor Grid Size: {:.0}", rules.editor.grid_size),
+                    format!(
+                        "Vignette: {}",
+                        if rules.vignette_enabled { "On" } else { "Off" }
+                    ),
                     format!("AI Preset: {}", ai_preset_name),
                     format!("Layout Preset: {}", layout_preset_name),
+                    format!("Acceleration: {:.0}", rules.player_acceleration),
+                    format!("Friction: {:.0}", rules.player_friction),
+                    format!("Air Acceleration: {:.0}", rules.player_air_acceleration),
+                    format!("Coyote Time: {:.2}s", rules.player_coyote_time),
+                    format!("Jump Buffer: {:.2}s", rules.player_jump_buffer),
+                    format!(
+                        "Variable Jump: {}",
+                        if rules.player_variable_jump { "On" } else { "Off" }
+                    ),
+                    format!("Air Jumps: {}", rules.player_air_jumps),
+                    format!(
+                        "Wall Slide: {}",
+                        if rules.player_wall_slide_enabled { "On" } else { "Off" }
+                    ),
+                    format!(
+                        "Wall Jump: {}",
+                        if rules.player_wall_jump_enabled { "On" } else { "Off" }
+                    ),
+                    format!(
+                        "Dash: {}",
+                        if rules.player_dash_enabled { "On" } else { "Off" }
+                    ),
+                    format!("Dash Speed: {:.0}", rules.player_dash_speed),
+                    format!("Dash Cooldown: {:.2}s", rules.player_dash_cooldown),
                 ];
 
-                for (i, text) in items.iter().enumerate() {
+                // Show a window of items that follows the selection
+                const VISIBLE_ITEMS: usize = 9;
+                let first = (rules_menu_index.max(0) as usize)
+                    .saturating_sub(VISIBLE_ITEMS / 2)
+                    .min(items.len().saturating_sub(VISIBLE_ITEMS));
+                for (row, (i, text)) in items
+                    .iter()
+                    .enumerate()
+                    .skip(first)
+                    .take(VISIBLE_ITEMS)
+                    .enumerate()
+                {
                     let color = if rules_menu_index == i as i32 { GREEN } else { GRAY };
                     draw_text(
                         text,
                         cx - 260.0,
-                        cy - 20.0 + i as f32 * 26.0,
+                        cy - 20.0 + row as f32 * 26.0,
                         22.0,
                         color,
                     );
@@ -2246,6 +2757,14 @@ async fn main() {
                     3 => "Move Right (alt)",
                     4 => "Jump (primary)",
                     5 => "Jump (alt)",
+                    6 => "Fire (primary)",
+                    7 => "Fire (alt)",
+                    8 => "Dash (primary)",
+                    9 => "Dash (alt)",
+                    10 => "Climb Up (primary)",
+                    11 => "Climb Up (alt)",
+                    12 => "Climb Down (primary)",
+                    13 => "Climb Down (alt)",
                     _ => "Done",
                 };
 
@@ -2265,21 +2784,30 @@ async fn main() {
                 draw_text(title, cx - 140.0, cy - 100.0, 36.0, YELLOW);
 
                 let scheme = rules.control_scheme.to_lowercase();
-                let lines: [&str; 4] = match scheme.as_str() {
+                let lines: [&str; 7] = match scheme.as_str() {
                     "wasd" => [
                         "Movement: A/D to move",
-                        "Jump: Space or W",
+                        "Jump: Space or W (hold for higher, again in the air)",
+                        "Fire: J",
+                        "Dash: K",
+                        "Climb ladders and vines: W/S (Space lets go)",
                         "Pause: Esc",
                         "Press Enter or Esc to return",
                     ],
                     "arrows" => [
                         "Movement: Left/Right arrows",
-                        "Jump: Up arrow",
+                        "Jump: Up arrow (hold for higher, again in the air)",
+                        "Fire: X",
+                        "Dash: Z",
+                        "Climb ladders and vines: Up/Down arrows",
                         "Pause: Esc",
                         "Press Enter or Esc to return",
                     ],
                     "custom" => [
                         "Movement & jump use custom bindings",
+                        "Fire: custom binding (default J)",
+                        "Dash: custom binding (default K)",
+                        "Climb: custom bindings (default W/S)",
                         "See Settings > Rebind and rules.json",
                         "Pause: Esc",
                         "Press Enter or Esc to return",
@@ -2287,6 +2815,9 @@ async fn main() {
                     _ => [
                         "Movement: A/D or Left/Right arrows",
                         "Jump: Space or W / Up arrow",
+                        "Fire: J or X",
+                        "Dash: K or Z",
+                        "Climb ladders and vines: W/S or Up/Down (Space lets go)",
                         "Pause: Esc",
                         "Press Enter or Esc to return",
                     ],
@@ -2377,6 +2908,11 @@ async fn main() {
                 draw_text(&summary1, cx - 140.0, cy + 80.0, 24.0, WHITE);
                 draw_text(&summary2, cx - 140.0, cy + 110.0, 24.0, WHITE);
                 draw_text(&summary3, cx - 140.0, cy + 140.0, 24.0, WHITE);
+                let summary4 = format!(
+                    "Enemies defeated: {} | Jumps: {}",
+                    run_stats.enemies_killed, run_stats.jumps
+                );
+                draw_text(&summary4, cx - 140.0, cy + 170.0, 24.0, WHITE);
             }
             GameState::Won => {
                 let cx = screen_width() * 0.5;
@@ -2392,6 +2928,11 @@ async fn main() {
                 draw_text(&summary1, cx - 140.0, cy + 80.0, 24.0, WHITE);
                 draw_text(&summary2, cx - 140.0, cy + 110.0, 24.0, WHITE);
                 draw_text(&summary3, cx - 140.0, cy + 140.0, 24.0, WHITE);
+                let summary4 = format!(
+                    "Enemies defeated: {} | Jumps: {}",
+                    run_stats.enemies_killed, run_stats.jumps
+                );
+                draw_text(&summary4, cx - 140.0, cy + 170.0, 24.0, WHITE);
             }
             GameState::Playing => {}
             GameState::LevelEditor => {}
@@ -2409,6 +2950,15 @@ async fn main() {
     }
 }
 
+// Writes the rules to rules.json, unless they are a continued run's, which
+// stay with its save.
+fn persist_rules(rules: &GameRules, own_rules: &Option<(GameRules, u64)>) -> Result<(), String> {
+    if own_rules.is_some() {
+        return Ok(());
+    }
+    save_rules(RULES_PATH, rules)
+}
+
 fn update_music_volume(sounds: &Sounds, rules: &GameRules) {
     if let Some(m) = sounds.music.as_ref() {
         let volume = if rules.music_enabled { rules.music_volume } else { 0.0 };
@@ -2462,18 +3012,28 @@ fn make_scene(
     level: u32,
     base_seed: u64,
 ) -> Scene {
+    let screen_size = world_size(rules);
+    engine::generator::generate_scene_for_seed(assets, rules, level, screen_size, base_seed)
+}
+
+// The world size levels are generated for at the current window size.
+fn world_size(rules: &GameRules) -> Vec2 {
     let world_width_screens = rules.world_width_screens.max(1.0);
     let world_height_screens = rules.world_height_screens.max(1.0);
     let screen_w = screen_width();
     let screen_h = screen_height();
     let world_w = screen_w * world_width_screens;
     let world_h = screen_h * world_height_screens;
-    let screen_size = vec2(world_w, world_h);
-    crate::generator::generate_scene_for_seed(assets, rules, level, screen_size, base_seed)
+    vec2(world_w, world_h)
+}
+
+// 1-based, kept within the rules' profile count.
+fn save_profile(rules: &GameRules) -> u32 {
+    rules.save_profile.clamp(1, rules.save_profile_count.max(1))
 }
 
 fn remove_nearest_in_level(
-    level: &mut crate::generator::CustomLevel,
+    level: &mut engine::generator::CustomLevel,
     pos: Vec2,
     radius: f32,
 ) {
@@ -2481,7 +3041,7 @@ fn remove_nearest_in_level(
 }
 
 fn remove_nearest_in_level_category(
-    level: &mut crate::generator::CustomLevel,
+    level: &mut engine::generator::CustomLevel,
     pos: Vec2,
     radius: f32,
     category: i32,
@@ -2524,6 +3084,50 @@ fn remove_nearest_in_level_category(
         }
     }
 
+    // hazards (only when erasing anything)
+    if category < 0 {
+        for (i, h) in level.hazards.iter().enumerate() {
+            let d2 = (vec2(h.x, h.y) - pos).length_squared();
+            if d2 <= best_dist2 {
+                best_dist2 = d2;
+                best = Some((i, 6));
+            }
+        }
+    }
+
+    // zones (only when erasing anything)
+    if category < 0 {
+        for (i, z) in level.zones.iter().enumerate() {
+            let d2 = (vec2(z.x, z.y) - pos).length_squared();
+            if d2 <= best_dist2 {
+                best_dist2 = d2;
+                best = Some((i, 9));
+            }
+        }
+    }
+
+    // checkpoints (only when erasing anything)
+    if category < 0 {
+        for (i, c) in level.checkpoints.iter().enumerate() {
+            let d2 = (vec2(c.x, c.y) - pos).length_squared();
+            if d2 <= best_dist2 {
+                best_dist2 = d2;
+                best = Some((i, 7));
+            }
+        }
+    }
+
+    // level exit (category 3 or any)
+    if category < 0 || category == 3 {
+        if let Some(ref goal) = level.goal {
+            let d2 = (vec2(goal.x, goal.y) - pos).length_squared();
+            if d2 <= best_dist2 {
+                best_dist2 = d2;
+                best = Some((0, 8));
+            }
+        }
+    }
+
     // player start (category 0 or any)
     if (category < 0 || category == 0) && level.player_start.is_some() {
         if let Some(ref start) = level.player_start {
@@ -2546,11 +3150,82 @@ fn remove_nearest_in_level_category(
             3 => {
                 level.collectibles.remove(index);
             }
+            6 => {
+                level.hazards.remove(index);
+            }
+            7 => {
+                level.checkpoints.remove(index);
+            }
+            8 => level.goal = None,
+            9 => {
+                level.zones.remove(index);
+            }
             _ => {}
         }
     }
 }
 
+// Tile legend the editor paints with: the level's own legend, or a default
+// one built from the platform sprites when the level has no tile layer yet.
+fn editor_tile_legend(
+    level: Option<&engine::generator::CustomLevel>,
+    platform_sprites: &[&engine::assets::SpriteAsset],
+) -> Vec<engine::generator::CustomTileLegend> {
+    use engine::generator::CustomTileLegend;
+    use engine::scene::TileKind;
+
+    if let Some(map) = level.and_then(|l| l.tilemap.as_ref()) {
+        if !map.legend.is_empty() {
+            return map.legend.clone();
+        }
+    }
+
+    let sprite = |i: usize| {
+        platform_sprites
+            .get(i % platform_sprites.len().max(1))
+            .map(|s| s.name.clone())
+            .unwrap_or_default()
+    };
+    vec![
+        CustomTileLegend { symbol: '#', kind: TileKind::Solid, sprite: sprite(0) },
+        CustomTileLegend { symbol: '=', kind: TileKind::OneWay, sprite: sprite(1) },
+        CustomTileLegend { symbol: '^', kind: TileKind::Hazard, sprite: sprite(2) },
+        CustomTileLegend { symbol: '~', kind: TileKind::Decoration, sprite: sprite(3) },
+    ]
+}
+
+// Writes `symbol` into the tile row string under `pos`, growing the rows with
+// empty cells as needed. Cells above or left of the map origin are ignored.
+// Returns whether the cell changed.
+fn set_editor_tile(map: &mut engine::generator::CustomTileMap, pos: Vec2, symbol: char) -> bool {
+    let size = map.tile_size.max(1.0);
+    let local = (pos - vec2(map.x, map.y)) / size;
+    if local.x < 0.0 || local.y < 0.0 {
+        return false;
+    }
+    let (column, row) = (local.x as usize, local.y as usize);
+
+    if symbol == '.' && row >= map.rows.len() {
+        return false;
+    }
+    if map.rows.len() <= row {
+        map.rows.resize(row + 1, String::new());
+    }
+    let mut cells: Vec<char> = map.rows[row].chars().collect();
+    if cells.len() <= column {
+        if symbol == '.' {
+            return false;
+        }
+        cells.resize(column + 1, '.');
+    }
+    if cells[column] == symbol {
+        return false;
+    }
+    cells[column] = symbol;
+    map.rows[row] = cells.into_iter().collect();
+    true
+}
+
 fn random_seed_from_time() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     let now = SystemTime::now()
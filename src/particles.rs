@@ -0,0 +1,297 @@
+use macroquad::prelude::*;
+use ::rand::rngs::StdRng;
+use ::rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+// effects.json names particle emitters; the scene fires "jump", "hit" and
+// "pickup". Effects under "themes" replace the same-named ones when the
+// rules' theme matches, e.g.
+// {"effects": {"jump": {"count": 10, "speed": [60, 100]}},
+//  "themes": {"forest": {"jump": {"colors": [[0.4, 0.8, 0.3, 1.0]]}}}}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EffectsFile {
+    pub effects: HashMap<String, EmitterDef>,
+    pub themes: HashMap<String, HashMap<String, EmitterDef>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmitterMode {
+    // All particles at once.
+    #[default]
+    Burst,
+    // `rate` particles per second for `duration` seconds.
+    Continuous,
+}
+
+// Ranges are [min, max], rolled per particle. Angles are in degrees, 0
+// pointing right and 90 down. `colors` and `sizes` are keyframes spread
+// evenly over each particle's life.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmitterDef {
+    pub mode: EmitterMode,
+    // Burst size; 0 uses the rules' particle count for the effect.
+    pub count: u32,
+    pub rate: f32,
+    pub duration: f32,
+    pub angle: [f32; 2],
+    pub speed: [f32; 2],
+    pub lifetime: [f32; 2],
+    // Particles start up to this far from the origin.
+    pub radius: f32,
+    pub gravity: f32,
+    pub colors: Vec<[f32; 4]>,
+    pub sizes: Vec<f32>,
+    // PNG relative to the assets root, tinted by `colors`; particles are
+    // plain squares without one.
+    pub texture: Option<String>,
+}
+
+impl Default for EmitterDef {
+    fn default() -> Self {
+        Self {
+            mode: EmitterMode::Burst,
+            count: 0,
+            rate: 30.0,
+            duration: 0.5,
+            angle: [0.0, 360.0],
+            speed: [60.0, 60.0],
+            lifetime: [0.4, 0.4],
+            radius: 0.0,
+            gravity: 0.0,
+            colors: vec![[1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]],
+            sizes: vec![4.0],
+            texture: None,
+        }
+    }
+}
+
+pub struct ParticleEffect {
+    // Its key in effects.json, or the built-in effect's name.
+    pub name: String,
+    pub def: EmitterDef,
+    pub texture: Option<Texture2D>,
+}
+
+pub type Effects = HashMap<String, Arc<ParticleEffect>>;
+
+// Every effect from effects.json, ready to hand to a scene.
+#[derive(Clone, Default)]
+pub struct ParticleEffects {
+    pub effects: Effects,
+    pub themes: HashMap<String, Effects>,
+}
+
+impl ParticleEffects {
+    // The built-in effects, overridden by the file's and then by the
+    // theme's.
+    pub fn for_theme(&self, theme: &str) -> Effects {
+        let mut effects = builtin_effects();
+        effects.extend(self.effects.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if let Some(themed) = self.themes.get(&theme.to_lowercase()) {
+            effects.extend(themed.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        effects
+    }
+}
+
+// What the scene uses when effects.json doesn't define an effect.
+pub fn builtin_effects() -> Effects {
+    let effect = |name: &str, angle: [f32; 2], speed: f32, lifetime: f32| {
+        Arc::new(ParticleEffect {
+            name: name.to_string(),
+            def: EmitterDef {
+                angle,
+                speed: [speed, speed],
+                lifetime: [lifetime, lifetime],
+                ..EmitterDef::default()
+            },
+            texture: None,
+        })
+    };
+    HashMap::from([
+        ("jump".to_string(), effect("jump", [180.0, 360.0], 80.0, 0.35)),
+        ("hit".to_string(), effect("hit", [0.0, 360.0], 90.0, 0.5)),
+        ("pickup".to_string(), effect("pickup", [0.0, 360.0], 60.0, 0.4)),
+    ])
+}
+
+// A missing effects.json just means the built-in effects.
+pub fn load_effects_file(path: &str) -> Result<EffectsFile, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return Ok(EffectsFile::default()),
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {path}: {e}"))
+}
+
+// Resolves each emitter's texture (a path under `root`) with `texture`.
+pub fn build_particle_effects(
+    root: &str,
+    file: EffectsFile,
+    mut texture: impl FnMut(&str) -> Result<Texture2D, String>,
+) -> Result<ParticleEffects, String> {
+    let mut build = |defs: HashMap<String, EmitterDef>| -> Result<Effects, String> {
+        let mut effects = Effects::new();
+        for (name, def) in defs {
+            let tex = match &def.texture {
+                Some(path) => Some(texture(&format!("{root}/{path}"))?),
+                None => None,
+            };
+            let effect = ParticleEffect {
+                name: name.clone(),
+                def,
+                texture: tex,
+            };
+            effects.insert(name, Arc::new(effect));
+        }
+        Ok(effects)
+    };
+    let effects = build(file.effects)?;
+    let mut themes = HashMap::new();
+    for (theme, defs) in file.themes {
+        themes.insert(theme.to_lowercase(), build(defs)?);
+    }
+    Ok(ParticleEffects { effects, themes })
+}
+
+// Every texture path effects.json refers to, relative to the assets root.
+pub fn effect_texture_paths(file: &EffectsFile) -> Vec<String> {
+    file.effects
+        .values()
+        .chain(file.themes.values().flat_map(|defs| defs.values()))
+        .filter_map(|def| def.texture.clone())
+        .collect()
+}
+
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    // Seconds left, out of `max_lifetime`.
+    pub lifetime: f32,
+    pub max_lifetime: f32,
+    pub effect: Arc<ParticleEffect>,
+}
+
+impl Particle {
+    pub fn update(&mut self, dt: f32) {
+        self.velocity.y += self.effect.def.gravity * dt;
+        self.position += self.velocity * dt;
+        self.lifetime -= dt;
+    }
+
+    // 0 when spawned, 1 when expired.
+    fn age(&self) -> f32 {
+        (1.0 - self.lifetime / self.max_lifetime.max(0.001)).clamp(0.0, 1.0)
+    }
+
+    pub fn draw(&self, scale: f32) {
+        let def = &self.effect.def;
+        let t = self.age();
+        let c = keyframe(&def.colors, t, |a, b, t| {
+            [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+        })
+        .unwrap_or([1.0; 4]);
+        let color = Color::new(c[0], c[1], c[2], c[3]);
+        let size = keyframe(&def.sizes, t, |a, b, t| a + (b - a) * t).unwrap_or(4.0) * scale;
+        let (x, y) = (self.position.x - size / 2.0, self.position.y - size / 2.0);
+        match &self.effect.texture {
+            Some(texture) => draw_texture_ex(
+                texture,
+                x,
+                y,
+                color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(size, size)),
+                    ..Default::default()
+                },
+            ),
+            None => draw_rectangle(x, y, size, size, color),
+        }
+    }
+}
+
+// The value at `t` (0..1) of keyframes spread evenly over 0..1.
+fn keyframe<T: Copy>(keys: &[T], t: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    match keys.len() {
+        0 => None,
+        1 => Some(keys[0]),
+        n => {
+            let pos = t * (n - 1) as f32;
+            let i = (pos.floor() as usize).min(n - 2);
+            Some(lerp(keys[i], keys[i + 1], pos - i as f32))
+        }
+    }
+}
+
+// A continuous effect still spawning particles.
+pub struct Emitter {
+    pub effect: Arc<ParticleEffect>,
+    pub position: Vec2,
+    // Seconds left to emit.
+    pub remaining: f32,
+    // Fractional particles owed from previous steps.
+    carry: f32,
+}
+
+impl Emitter {
+    pub fn new(effect: Arc<ParticleEffect>, position: Vec2) -> Self {
+        Self {
+            remaining: effect.def.duration,
+            effect,
+            position,
+            carry: 0.0,
+        }
+    }
+
+    // Emits this step's share of particles.
+    pub fn update(&mut self, dt: f32, out: &mut Vec<Particle>, rng: &mut StdRng) {
+        self.carry += self.effect.def.rate.max(0.0) * dt;
+        let count = self.carry.floor();
+        self.carry -= count;
+        emit(out, &self.effect, self.position, count as u32, rng);
+        self.remaining -= dt;
+    }
+}
+
+// Spawns `count` particles of `effect` at `origin`.
+pub fn emit(
+    out: &mut Vec<Particle>,
+    effect: &Arc<ParticleEffect>,
+    origin: Vec2,
+    count: u32,
+    rng: &mut StdRng,
+) {
+    let def = &effect.def;
+    for _ in 0..count {
+        let angle = roll(rng, def.angle).to_radians();
+        let dir = vec2(angle.cos(), angle.sin());
+        let offset = if def.radius > 0.0 {
+            let a = rng.gen_range(0.0..std::f32::consts::TAU);
+            vec2(a.cos(), a.sin()) * rng.gen_range(0.0..def.radius)
+        } else {
+            Vec2::ZERO
+        };
+        let lifetime = roll(rng, def.lifetime).max(0.01);
+        out.push(Particle {
+            position: origin + offset,
+            velocity: dir * roll(rng, def.speed),
+            lifetime,
+            max_lifetime: lifetime,
+            effect: effect.clone(),
+        });
+    }
+}
+
+fn roll(rng: &mut StdRng, [min, max]: [f32; 2]) -> f32 {
+    if max > min {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
@@ -0,0 +1,234 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpKind {
+    // Faster running.
+    Speed,
+    // No damage from enemies, shots or hazards.
+    Invincibility,
+    // Higher jumps.
+    HighJump,
+    // Pulls nearby collectibles towards the player.
+    Magnet,
+    // Shots and stomps deal more damage.
+    DoubleDamage,
+    // Absorbs hits, one per charge.
+    Shield,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::Speed,
+        PowerUpKind::Invincibility,
+        PowerUpKind::HighJump,
+        PowerUpKind::Magnet,
+        PowerUpKind::DoubleDamage,
+        PowerUpKind::Shield,
+    ];
+
+    // Also the prefix of the sprites the generator picks for it.
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUpKind::Speed => "speed",
+            PowerUpKind::Invincibility => "invincibility",
+            PowerUpKind::HighJump => "high_jump",
+            PowerUpKind::Magnet => "magnet",
+            PowerUpKind::DoubleDamage => "double_damage",
+            PowerUpKind::Shield => "shield",
+        }
+    }
+
+    // The kind a sprite named like "shield_1" stands for.
+    pub fn from_sprite_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL.into_iter().find(|kind| name.starts_with(kind.name()))
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerUpKind::Speed => "Speed",
+            PowerUpKind::Invincibility => "Invincible",
+            PowerUpKind::HighJump => "High jump",
+            PowerUpKind::Magnet => "Magnet",
+            PowerUpKind::DoubleDamage => "Double damage",
+            PowerUpKind::Shield => "Shield",
+        }
+    }
+
+    // Tint for the player while the power-up is active.
+    pub fn color(self) -> Color {
+        match self {
+            PowerUpKind::Speed => SKYBLUE,
+            PowerUpKind::Invincibility => GOLD,
+            PowerUpKind::HighJump => LIME,
+            PowerUpKind::Magnet => VIOLET,
+            PowerUpKind::DoubleDamage => ORANGE,
+            PowerUpKind::Shield => Color::new(0.6, 0.8, 1.0, 1.0),
+        }
+    }
+}
+
+// What picking up a power-up that is already active does to its timer.
+// Shield charges add up either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PowerUpStacking {
+    // Starts the timer over.
+    #[default]
+    Refresh,
+    // Adds the new duration to what is left, up to `max_duration`.
+    Extend,
+}
+
+// The power-up a collectible grants. A zero duration uses the scene's
+// default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    #[serde(default)]
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PowerUpConfig {
+    pub duration: f32,
+    pub max_duration: f32,
+    pub stacking: PowerUpStacking,
+    pub speed_multiplier: f32,
+    pub jump_multiplier: f32,
+    pub magnet_radius: f32,
+    pub magnet_speed: f32,
+    pub damage_multiplier: u32,
+    // Charges per shield pickup, and the most the player can hold.
+    pub shield_charges: u32,
+    pub shield_max_charges: u32,
+}
+
+impl Default for PowerUpConfig {
+    fn default() -> Self {
+        Self {
+            duration: 8.0,
+            max_duration: 20.0,
+            stacking: PowerUpStacking::Refresh,
+            speed_multiplier: 1.5,
+            jump_multiplier: 1.3,
+            magnet_radius: 160.0,
+            magnet_speed: 320.0,
+            damage_multiplier: 2,
+            shield_charges: 1,
+            shield_max_charges: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ActivePowerUp {
+    pub kind: PowerUpKind,
+    // Seconds left, and the length of the last pickup for HUD bars.
+    pub remaining: f32,
+    pub duration: f32,
+    // Shield only.
+    pub charges: u32,
+}
+
+// The player's active power-ups, at most one per kind, in pickup order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PowerUps {
+    active: Vec<ActivePowerUp>,
+}
+
+impl PowerUps {
+    pub fn apply(&mut self, power_up: PowerUp, config: &PowerUpConfig) {
+        let duration = if power_up.duration > 0.0 {
+            power_up.duration
+        } else {
+            config.duration
+        };
+        let charges = if power_up.kind == PowerUpKind::Shield {
+            config.shield_charges.max(1)
+        } else {
+            0
+        };
+        let max_charges = config.shield_max_charges.max(1);
+
+        if let Some(active) = self.active.iter_mut().find(|a| a.kind == power_up.kind) {
+            match config.stacking {
+                PowerUpStacking::Refresh => {
+                    active.remaining = duration;
+                    active.duration = duration;
+                }
+                PowerUpStacking::Extend => {
+                    active.remaining =
+                        (active.remaining + duration).min(config.max_duration.max(duration));
+                    active.duration = active.duration.max(active.remaining);
+                }
+            }
+            active.charges = (active.charges + charges).min(max_charges);
+            return;
+        }
+        self.active.push(ActivePowerUp {
+            kind: power_up.kind,
+            remaining: duration,
+            duration,
+            charges: charges.min(max_charges),
+        });
+    }
+
+    // Counts the timers down and returns the kinds that ran out.
+    pub fn tick(&mut self, dt: f32) -> Vec<PowerUpKind> {
+        let mut expired = Vec::new();
+        self.active.retain_mut(|a| {
+            a.remaining -= dt;
+            if a.remaining <= 0.0 {
+                expired.push(a.kind);
+                return false;
+            }
+            true
+        });
+        expired
+    }
+
+    // Spends a shield charge if there is one; the shield ends with its last
+    // charge.
+    pub fn use_shield(&mut self) -> bool {
+        let Some(index) = self
+            .active
+            .iter()
+            .position(|a| a.kind == PowerUpKind::Shield && a.charges > 0)
+        else {
+            return false;
+        };
+        self.active[index].charges -= 1;
+        if self.active[index].charges == 0 {
+            self.active.remove(index);
+        }
+        true
+    }
+
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.active.iter().any(|a| a.kind == kind)
+    }
+
+    pub fn active(&self) -> &[ActivePowerUp] {
+        &self.active
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    // Player tint: cycles through the active power-up colors, blinking as
+    // each is about to run out. `None` with nothing active.
+    pub fn tint(&self, time: f32) -> Option<Color> {
+        if self.active.is_empty() {
+            return None;
+        }
+        let index = (time * 4.0) as usize % self.active.len();
+        let active = &self.active[index];
+        if active.remaining < 1.5 && ((time * 10.0) as u32).is_multiple_of(2) {
+            return None;
+        }
+        Some(active.kind.color())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::registry::EntityId;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Who fired a projectile; it only hurts the other side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Team {
+    Player,
+    Enemy,
+}
+
+pub struct Projectile {
+    pub team: Team,
+    // The entity that fired it, if any; it may have been removed since.
+    pub owner: Option<EntityId>,
+    // Drawn as a plain square when there is no sprite.
+    pub texture: Option<Texture2D>,
+    // Region of `texture` to draw; `None` draws all of it.
+    pub source: Option<Rect>,
+    pub size: Vec2,
+    pub position: Vec2,
+    pub prev_position: Vec2,
+    pub velocity: Vec2,
+    pub gravity_scale: f32,
+    // Seconds left before the projectile expires.
+    pub lifetime: f32,
+    pub damage: u32,
+    // How many more targets it can pass through after the next hit.
+    pub pierce: u32,
+    // Whether it stops on platforms and solid tiles.
+    pub collides: bool,
+}
+
+impl Projectile {
+    pub fn rect(&self, scale: f32) -> Rect {
+        let size = self.size * scale;
+        Rect::new(
+            self.position.x - size.x / 2.0,
+            self.position.y - size.y / 2.0,
+            size.x,
+            size.y,
+        )
+    }
+
+    pub fn draw(&self, position: Vec2, scale: f32) {
+        let size = self.size * scale;
+        let color = match self.team {
+            Team::Player => SKYBLUE,
+            Team::Enemy => YELLOW,
+        };
+        match &self.texture {
+            Some(texture) => draw_texture_ex(
+                texture,
+                position.x - size.x / 2.0,
+                position.y - size.y / 2.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(size),
+                    source: self.source,
+                    rotation: self.velocity.y.atan2(self.velocity.x),
+                    ..Default::default()
+                },
+            ),
+            None => draw_rectangle(
+                position.x - size.x / 2.0,
+                position.y - size.y / 2.0,
+                size.x,
+                size.y,
+                color,
+            ),
+        }
+    }
+}
+
+// Everything about a kind of shot except where it goes. The scene keeps one
+// for the player and one for enemies.
+#[derive(Clone)]
+pub struct ProjectileSpec {
+    pub texture: Option<Texture2D>,
+    pub source: Option<Rect>,
+    pub size: Vec2,
+    pub speed: f32,
+    pub damage: u32,
+    pub gravity_scale: f32,
+    pub lifetime: f32,
+    pub pierce: u32,
+    pub collides: bool,
+}
+
+impl ProjectileSpec {
+    pub fn new(speed: f32, damage: u32) -> Self {
+        Self {
+            texture: None,
+            source: None,
+            size: vec2(8.0, 8.0),
+            speed,
+            damage,
+            gravity_scale: 0.0,
+            lifetime: 3.0,
+            pierce: 0,
+            collides: true,
+        }
+    }
+
+    // A projectile fired from `origin` along `direction` (need not be
+    // normalized).
+    pub fn spawn(&self, team: Team, origin: Vec2, direction: Vec2) -> Projectile {
+        Projectile {
+            team,
+            owner: None,
+            texture: self.texture.clone(),
+            source: self.source,
+            size: self.size,
+            position: origin,
+            prev_position: origin,
+            velocity: direction.normalize_or_zero() * self.speed,
+            gravity_scale: self.gravity_scale,
+            lifetime: self.lifetime,
+            damage: self.damage.max(1),
+            pierce: self.pierce,
+            collides: self.collides,
+        }
+    }
+}
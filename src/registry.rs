@@ -0,0 +1,90 @@
+// Generational handles for scene entities. An id stays valid while its
+// entity is alive; once the entity is removed its slot may be reused, but
+// with a new generation, so stale ids never resolve to a different entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+impl EntityId {
+    // Never handed out; entities built outside a scene carry it until the
+    // scene adds them.
+    pub const INVALID: EntityId = EntityId {
+        index: u32::MAX,
+        generation: 0,
+    };
+
+    pub fn is_valid(self) -> bool {
+        self != Self::INVALID
+    }
+}
+
+struct Slot {
+    generation: u32,
+    // Index into the scene's entity list; `None` while the entity is
+    // waiting to be spawned or after it was removed.
+    position: Option<usize>,
+    alive: bool,
+}
+
+#[derive(Default)]
+pub struct EntityRegistry {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl EntityRegistry {
+    pub fn allocate(&mut self) -> EntityId {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.alive = true;
+            slot.position = None;
+            return EntityId {
+                index,
+                generation: slot.generation,
+            };
+        }
+        self.slots.push(Slot {
+            generation: 0,
+            position: None,
+            alive: true,
+        });
+        EntityId {
+            index: self.slots.len() as u32 - 1,
+            generation: 0,
+        }
+    }
+
+    // Frees the id's slot for reuse. Stale ids are ignored.
+    pub fn release(&mut self, id: EntityId) {
+        if !self.is_alive(id) {
+            return;
+        }
+        let slot = &mut self.slots[id.index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.position = None;
+        slot.alive = false;
+        self.free.push(id.index);
+    }
+
+    pub fn is_alive(&self, id: EntityId) -> bool {
+        self.slot(id).is_some()
+    }
+
+    pub fn position(&self, id: EntityId) -> Option<usize> {
+        self.slot(id).and_then(|slot| slot.position)
+    }
+
+    pub fn set_position(&mut self, id: EntityId, position: usize) {
+        if self.is_alive(id) {
+            self.slots[id.index as usize].position = Some(position);
+        }
+    }
+
+    fn slot(&self, id: EntityId) -> Option<&Slot> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.alive && slot.generation == id.generation)
+    }
+}
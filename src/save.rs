@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::events::RunStats;
+use crate::generator::GameRules;
+use crate::snapshot::SceneSnapshot;
+
+// Bump when `SaveGame` changes in a way older files can't be read as; add
+// the upgrade from the old version to `load_save`.
+pub const SAVE_VERSION: u32 = 1;
+
+const SAVES_DIR: &str = "assets/config/saves";
+
+// A run in progress, kept in a profile's save slot so it can be continued
+// after quitting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub seed: u64,
+    pub level: u32,
+    pub run_score: u32,
+    pub run_time: f32,
+    pub total_collected: u32,
+    pub stats: RunStats,
+    // Size of the world the level was generated for, which depends on the
+    // window size at the time.
+    pub world_size: [f32; 2],
+    pub rules: GameRules,
+    // The level mid-play; without it the level restarts from the beginning.
+    pub scene: Option<SceneSnapshot>,
+    // Seconds since the Unix epoch.
+    pub saved_at: u64,
+}
+
+impl SaveGame {
+    // A save at the start of `level` with nothing collected yet.
+    pub fn new(rules: &GameRules, seed: u64, level: u32, world_size: [f32; 2]) -> Self {
+        Self {
+            seed,
+            level,
+            run_score: 0,
+            run_time: 0.0,
+            total_collected: 0,
+            stats: RunStats::default(),
+            world_size,
+            rules: rules.clone(),
+            scene: None,
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+// On disk the save is wrapped with its format version and a checksum of
+// the data, so truncated or hand-damaged files are refused instead of
+// loading half a run. The data is kept as the exact text that was hashed,
+// since floats don't always reparse to the same text.
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    checksum: String,
+    data: Box<RawValue>,
+}
+
+pub fn save_path(profile: u32) -> PathBuf {
+    Path::new(SAVES_DIR).join(format!("profile_{profile}.json"))
+}
+
+// Writes through a temporary file so a crash mid-write leaves the old save.
+pub fn save_game(profile: u32, save: &SaveGame) -> Result<String, String> {
+    if let Err(e) = fs::create_dir_all(SAVES_DIR) {
+        return Err(format!("Failed to create saves dir {SAVES_DIR}: {e}"));
+    }
+    let text = encode_save(save)?;
+    let path = save_path(profile);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, text).map_err(|e| format!("Failed to write save {:?}: {e}", tmp))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace save {:?}: {e}", path))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// `None` when the profile has no save yet.
+pub fn load_save(profile: u32) -> Result<Option<SaveGame>, String> {
+    let path = save_path(profile);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read save {:?}: {e}", path)),
+    };
+    decode_save(&text)
+        .map(Some)
+        .map_err(|e| format!("Save {:?}: {e}", path))
+}
+
+// The save file's contents.
+pub fn encode_save(save: &SaveGame) -> Result<String, String> {
+    let data = serde_json::to_string(save)
+        .and_then(RawValue::from_string)
+        .map_err(|e| format!("Failed to serialize save: {e}"))?;
+    let file = SaveFile {
+        version: SAVE_VERSION,
+        checksum: checksum(data.get()),
+        data,
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize save: {e}"))
+}
+
+pub fn decode_save(text: &str) -> Result<SaveGame, String> {
+    let file: SaveFile = serde_json::from_str(text).map_err(|e| format!("corrupted: {e}"))?;
+    if file.version > SAVE_VERSION {
+        return Err(format!(
+            "from a newer version ({} > {SAVE_VERSION})",
+            file.version
+        ));
+    }
+    if checksum(file.data.get()) != file.checksum {
+        return Err("corrupted: checksum mismatch".to_string());
+    }
+    serde_json::from_str(file.data.get()).map_err(|e| format!("failed to parse: {e}"))
+}
+
+pub fn delete_save(profile: u32) -> Result<(), String> {
+    let path = save_path(profile);
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete save {:?}: {e}", path)),
+    }
+}
+
+// FNV-1a over the data's text as written. Catches corruption, not
+// tampering.
+fn checksum(data: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
@@ -1,23 +1,380 @@
 use macroquad::prelude::*;
-use macroquad::audio::{self, Sound, PlaySoundParams};
-
+use macroquad::audio::Sound;
+use crate::animation::{AnimState, Animator};
+use crate::assets::Assets;
+use crate::background::BackgroundLayer;
+use crate::camera::Camera;
+use crate::events::GameEvent;
+use crate::input::{InputFrame, InputSource};
+use crate::lighting::{Light, LightingConfig};
+use crate::particles::{builtin_effects, emit, EmitterMode, Effects, Emitter, Particle};
+use crate::powerup::{PowerUp, PowerUpConfig, PowerUpKind, PowerUps};
+use crate::projectile::{Projectile, ProjectileSpec, Team};
+use crate::registry::{EntityId, EntityRegistry};
+use crate::snapshot::{
+    EmitterSnapshot, EntitySnapshot, ParticleSnapshot, PlatformSnapshot, ProjectileSnapshot,
+    SceneSnapshot,
+};
+use crate::spatial::SpatialGrid;
+use ::rand::rngs::StdRng;
+use ::rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EntityKind {
     Player,
     Enemy,
     Collectible,
-    Projectile,
+    Hazard,
+    // Touching one makes it the player's respawn point.
+    Checkpoint,
+    // The level exit; touching it completes the level.
+    Goal,
+}
+
+// How an enemy moves. Speeds come from the scene (`enemy_speed`,
+// `enemy_jump_strength`); the parameters here are per enemy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EnemyBehavior {
+    // Walks until it hits a wall or the world edge, then turns around.
+    #[default]
+    Patrol,
+    // Like Patrol, but also turns around instead of walking off a ledge.
+    EdgePatrol,
+    // Walks toward the player while they are within `range` horizontally.
+    Chase {
+        #[serde(default = "default_chase_range")]
+        range: f32,
+    },
+    // Orbits its spawn point; ignores gravity and platforms.
+    Circle {
+        #[serde(default = "default_circle_radius")]
+        radius: f32,
+        #[serde(default = "default_circle_speed")]
+        speed: f32,
+    },
+    // Patrols in the air, bobbing on a sine wave around its spawn height.
+    Flyer {
+        #[serde(default = "default_flyer_amplitude")]
+        amplitude: f32,
+        #[serde(default = "default_flyer_speed")]
+        speed: f32,
+    },
+    // Stands still and shoots at the player.
+    Turret,
+    // Jumps toward the player when they are within `range`, at most once
+    // every `interval` seconds.
+    Hopper {
+        #[serde(default = "default_chase_range")]
+        range: f32,
+        #[serde(default = "default_hopper_interval")]
+        interval: f32,
+    },
+    // Waits without moving until the player comes within `range`, then
+    // chases them for good (see `Entity::awake`).
+    Ambusher {
+        #[serde(default = "default_ambush_range")]
+        range: f32,
+    },
+}
+
+impl EnemyBehavior {
+    pub const NAMES: [&'static str; 8] = [
+        "patrol",
+        "edge_patrol",
+        "chase",
+        "circle",
+        "flyer",
+        "turret",
+        "hopper",
+        "ambusher",
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Patrol => "patrol",
+            Self::EdgePatrol => "edge_patrol",
+            Self::Chase { .. } => "chase",
+            Self::Circle { .. } => "circle",
+            Self::Flyer { .. } => "flyer",
+            Self::Turret => "turret",
+            Self::Hopper { .. } => "hopper",
+            Self::Ambusher { .. } => "ambusher",
+        }
+    }
+}
+
+fn default_chase_range() -> f32 {
+    320.0
+}
+
+fn default_circle_radius() -> f32 {
+    40.0
+}
+
+fn default_circle_speed() -> f32 {
+    1.2
+}
+
+fn default_flyer_amplitude() -> f32 {
+    24.0
+}
+
+fn default_flyer_speed() -> f32 {
+    2.0
+}
+
+fn default_hopper_interval() -> f32 {
+    1.5
+}
+
+fn default_ambush_range() -> f32 {
+    200.0
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HazardKind {
+    #[default]
+    Spikes,
+    Lava,
+    Saw,
+    // Invisible; usually lethal, e.g. under the world.
+    KillZone,
+}
+
+impl HazardKind {
+    // Hazard sprites are picked by this prefix, e.g. "spikes_1".
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Spikes => "spikes",
+            Self::Lava => "lava",
+            Self::Saw => "saw",
+            Self::KillZone => "kill_zone",
+        }
+    }
+}
+
+// How a hazard moves around its spawn point. Speeds are in radians/s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HazardMotion {
+    #[default]
+    Static,
+    // Swings back and forth by up to (dx, dy) either side of its spawn.
+    Line {
+        #[serde(default)]
+        dx: f32,
+        #[serde(default)]
+        dy: f32,
+        #[serde(default = "default_hazard_speed")]
+        speed: f32,
+    },
+    // Orbits its spawn point.
+    Circle {
+        #[serde(default = "default_circle_radius")]
+        radius: f32,
+        #[serde(default = "default_hazard_speed")]
+        speed: f32,
+    },
+}
+
+fn default_hazard_speed() -> f32 {
+    2.0
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    // Health taken per touch, through the usual hit invincibility.
+    pub damage: u32,
+    // Kills outright, even while invincible.
+    pub lethal: bool,
+    pub motion: HazardMotion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneKind {
+    // Climbable with up/down; jump lets go.
+    Ladder,
+    Vine,
+    // Buoyancy and drag; jump swims upwards.
+    Water,
+    // Scales gravity by the zone's `gravity_scale`.
+    LowGravity,
+}
+
+impl ZoneKind {
+    pub fn climbable(self) -> bool {
+        matches!(self, ZoneKind::Ladder | ZoneKind::Vine)
+    }
+}
+
+// A box in world space that changes the player's physics inside it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zone {
+    pub kind: ZoneKind,
+    pub rect: Rect,
+    // Low-gravity zones only; multiplies gravity for anything inside.
+    pub gravity_scale: f32,
+}
+
+impl Zone {
+    pub fn new(kind: ZoneKind, rect: Rect) -> Self {
+        Self {
+            kind,
+            rect,
+            gravity_scale: 1.0,
+        }
+    }
+
+    // Climbable zones are grabbed with the body's center line over them;
+    // touching the top counts, so a ladder can be taken from the platform
+    // it leads to.
+    fn reaches(&self, center_x: f32, body: &Rect) -> bool {
+        center_x >= self.rect.x
+            && center_x <= self.rect.right()
+            && body.bottom() >= self.rect.y
+            && body.y <= self.rect.bottom()
+    }
+
+    pub fn draw(&self, time: f32) {
+        let r = self.rect;
+        match self.kind {
+            ZoneKind::Ladder => {
+                let color = Color::new(0.55, 0.36, 0.2, 1.0);
+                for x in [r.x + 2.0, r.right() - 2.0] {
+                    draw_line(x, r.y, x, r.bottom(), 3.0, color);
+                }
+                let mut y = r.y + 8.0;
+                while y < r.bottom() {
+                    draw_line(r.x + 2.0, y, r.right() - 2.0, y, 2.0, color);
+                    y += 16.0;
+                }
+            }
+            ZoneKind::Vine => {
+                let x = r.center().x;
+                let mut y = r.y;
+                while y < r.bottom() {
+                    let next = (y + 8.0).min(r.bottom());
+                    let sway = |y: f32| (y * 0.08 + time).sin() * r.w * 0.25;
+                    draw_line(x + sway(y), y, x + sway(next), next, 3.0, DARKGREEN);
+                    if ((y - r.y) as u32).is_multiple_of(24) {
+                        draw_circle(x + sway(y) + 4.0, y, 3.0, GREEN);
+                    }
+                    y = next;
+                }
+            }
+            ZoneKind::Water => {
+                draw_rectangle(r.x, r.y, r.w, r.h, Color::new(0.2, 0.45, 0.9, 0.45));
+                let surface = Color::new(0.7, 0.85, 1.0, 0.8);
+                draw_line(r.x, r.y, r.right(), r.y, 2.0, surface);
+            }
+            ZoneKind::LowGravity => {
+                draw_rectangle(r.x, r.y, r.w, r.h, Color::new(0.6, 0.4, 0.9, 0.12));
+                // Motes drifting upwards
+                let mote = Color::new(0.8, 0.7, 1.0, 0.5);
+                for i in 0..(r.w * r.h / 4000.0).clamp(1.0, 40.0) as u32 {
+                    let fx = (i as f32 * 0.618).fract();
+                    let fy = (i as f32 * 0.377 - time * 0.1).rem_euclid(1.0);
+                    draw_circle(r.x + fx * r.w, r.y + fy * r.h, 2.0, mote);
+                }
+            }
+        }
+    }
+}
+
+// Gravity multiplier at `point`, from the low-gravity zones containing it.
+fn zone_gravity_scale(zones: &[Zone], point: Vec2) -> f32 {
+    zones
+        .iter()
+        .filter(|z| z.kind == ZoneKind::LowGravity && z.rect.contains(point))
+        .map(|z| z.gravity_scale)
+        .product()
 }
 
 pub struct Entity {
+    // Assigned when the scene adds or spawns the entity.
+    pub id: EntityId,
     pub kind: EntityKind,
+    // Sprite the entity was made from.
+    pub name: String,
+    // Optional label for scripts and lookups, e.g. from a custom level.
+    pub tag: Option<String>,
     pub texture: Texture2D,
+    // Region of `texture` to draw, for sprites packed in an atlas; `None`
+    // draws the whole texture.
+    pub source: Option<Rect>,
+    pub size: Vec2,
     pub position: Vec2,
+    pub prev_position: Vec2,
     pub velocity: Vec2,
     pub value: u32,
     pub health_value: u32,
     pub base_position: Vec2,
     pub phase: f32,
     pub jumping: bool,
+    // Velocity inherited from a moving platform when jumping off it; added
+    // on top of the entity's own movement until it lands again.
+    pub momentum: Vec2,
+    // Enemies only.
+    pub behavior: EnemyBehavior,
+    // Per-entity countdown used by behaviours (e.g. hopper jumps).
+    pub cooldown: f32,
+    // Set once an ambusher has spotted the player.
+    pub awake: bool,
+    // Enemies only; stomps take this down and the enemy dies at zero.
+    pub health: u32,
+    // Seconds left of the death effect; the enemy is harmless meanwhile and
+    // is removed when it runs out.
+    pub dying: Option<f32>,
+    // Enemies can't be hurt again until this runs out, so a piercing shot
+    // only hits each of them once.
+    pub hurt_timer: f32,
+    // 1.0 facing right, -1.0 facing left; the player shoots this way.
+    pub facing: f32,
+    // Drawn instead of `texture` when the sprite has animations.
+    pub animator: Option<Animator>,
+    // Hazards only.
+    pub hazard: Hazard,
+    // Collectibles only; picking it up grants this power-up.
+    pub power_up: Option<PowerUp>,
+}
+
+impl Entity {
+    pub fn new(kind: EntityKind, texture: Texture2D, size: Vec2, position: Vec2) -> Self {
+        Self {
+            id: EntityId::INVALID,
+            kind,
+            name: String::new(),
+            tag: None,
+            texture,
+            source: None,
+            size,
+            position,
+            prev_position: position,
+            velocity: Vec2::ZERO,
+            value: 0,
+            health_value: 0,
+            base_position: position,
+            phase: 0.0,
+            jumping: false,
+            momentum: Vec2::ZERO,
+            behavior: EnemyBehavior::Patrol,
+            cooldown: 0.0,
+            awake: false,
+            health: 1,
+            dying: None,
+            hurt_timer: 0.0,
+            facing: 1.0,
+            animator: None,
+            hazard: Hazard::default(),
+            power_up: None,
+        }
+    }
 }
 
 pub struct Sounds {
@@ -27,6 +384,18 @@ pub struct Sounds {
     pub music: Option<Sound>,
 }
 
+impl Sounds {
+    // No audio at all, for headless runs where no audio device is available.
+    pub fn silent() -> Self {
+        Self {
+            jump: None,
+            hit: None,
+            pickup: None,
+            music: None,
+        }
+    }
+}
+
 pub struct InputConfig {
     pub move_left_primary: KeyCode,
     pub move_left_alt: Option<KeyCode>,
@@ -34,28 +403,321 @@ pub struct InputConfig {
     pub move_right_alt: Option<KeyCode>,
     pub jump_primary: KeyCode,
     pub jump_alt: Option<KeyCode>,
+    pub fire_primary: KeyCode,
+    pub fire_alt: Option<KeyCode>,
+    pub dash_primary: KeyCode,
+    pub dash_alt: Option<KeyCode>,
+    // Climbing ladders and vines.
+    pub up_primary: KeyCode,
+    pub up_alt: Option<KeyCode>,
+    pub down_primary: KeyCode,
+    pub down_alt: Option<KeyCode>,
+}
+
+// Tuning for the player's movement. Accelerations and frictions are in
+// pixels/s²; zero means the speed changes instantly. Times are in seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct MovementConfig {
+    pub acceleration: f32,
+    pub friction: f32,
+    pub air_acceleration: f32,
+    pub air_friction: f32,
+    // Grace period after walking off a ledge during which a jump still counts
+    // as a ground jump.
+    pub coyote_time: f32,
+    // A jump pressed this long before landing fires on landing.
+    pub jump_buffer: f32,
+    // Releasing jump while rising multiplies the upward speed by `jump_cut`.
+    pub variable_jump: bool,
+    pub jump_cut: f32,
+    pub air_jumps: u32,
+    // Air jump strength as a fraction of the ground jump.
+    pub air_jump_multiplier: f32,
+    pub wall_slide_enabled: bool,
+    pub wall_slide_speed: f32,
+    pub wall_jump_enabled: bool,
+    pub wall_jump_strength: f32,
+    pub wall_jump_push: f32,
+    // Steering is ignored this long after a wall jump so the push away from
+    // the wall isn't cancelled straight away.
+    pub wall_jump_lock: f32,
+    pub dash_enabled: bool,
+    pub dash_speed: f32,
+    pub dash_duration: f32,
+    pub dash_cooldown: f32,
+    pub climb_speed: f32,
+    // Horizontal speed in water as a fraction of running speed.
+    pub swim_speed_scale: f32,
+    // Upward speed of a jump pressed in water; it can be repeated.
+    pub swim_stroke_strength: f32,
+    // Fraction of gravity water cancels; above 1 floats the player up.
+    pub water_buoyancy: f32,
+    // Fraction of vertical speed lost per second in water.
+    pub water_drag: f32,
+}
+
+impl Default for MovementConfig {
+    // Instant speed changes and a single ground jump, as before these
+    // options existed.
+    fn default() -> Self {
+        Self {
+            acceleration: 0.0,
+            friction: 0.0,
+            air_acceleration: 0.0,
+            air_friction: 0.0,
+            coyote_time: 0.0,
+            jump_buffer: 0.0,
+            variable_jump: false,
+            jump_cut: 0.5,
+            air_jumps: 0,
+            air_jump_multiplier: 1.0,
+            wall_slide_enabled: false,
+            wall_slide_speed: 120.0,
+            wall_jump_enabled: false,
+            wall_jump_strength: 400.0,
+            wall_jump_push: 260.0,
+            wall_jump_lock: 0.15,
+            dash_enabled: false,
+            dash_speed: 600.0,
+            dash_duration: 0.15,
+            dash_cooldown: 0.6,
+            climb_speed: 160.0,
+            swim_speed_scale: 0.6,
+            swim_stroke_strength: 300.0,
+            water_buoyancy: 0.75,
+            water_drag: 3.0,
+        }
+    }
+}
+
+// Per-run timers and counters behind `MovementConfig`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PlayerMotion {
+    coyote: f32,
+    jump_buffer: f32,
+    air_jumps_left: u32,
+    // Set by a jump until the player starts falling; only then can releasing
+    // jump cut it short.
+    rising: bool,
+    wall_lock: f32,
+    dash_timer: f32,
+    dash_cooldown: f32,
+    dash_dir: f32,
+    air_dash_used: bool,
+    // On a ladder or vine; gravity is off until it lets go.
+    climbing: bool,
 }
 
 pub struct Platform {
+    // Sprite the platform was made from.
+    pub name: String,
     pub texture: Texture2D,
+    pub source: Option<Rect>,
+    pub size: Vec2,
     pub position: Vec2,
+    pub prev_position: Vec2,
+    // Displacement over the last step divided by its duration.
+    pub velocity: Vec2,
     pub base_position: Vec2,
     pub phase: f32,
     pub moving: bool,
     pub vertical: bool,
+    // One-way platforms can be jumped through from below and only block
+    // entities landing on top; solid ones block from every side.
+    pub one_way: bool,
 }
 
-pub struct Particle {
-    pub position: Vec2,
-    pub velocity: Vec2,
-    pub lifetime: f32,
+impl Platform {
+    pub fn new(texture: Texture2D, size: Vec2, position: Vec2) -> Self {
+        Self {
+            name: String::new(),
+            texture,
+            source: None,
+            size,
+            position,
+            prev_position: position,
+            velocity: Vec2::ZERO,
+            base_position: position,
+            phase: 0.0,
+            moving: false,
+            vertical: false,
+            one_way: true,
+        }
+    }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileKind {
+    #[default]
+    Empty,
+    Solid,
+    OneWay,
+    // Hurts the player on contact; doesn't block movement.
+    Hazard,
+    // Drawn only.
+    Decoration,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tile {
+    pub kind: TileKind,
+    // Index into `TileMap::textures`.
+    pub texture: u16,
+}
+
+// Static level geometry on a uniform grid. Cheaper than a platform per block
+// for large maps: collision and drawing only visit the cells in range.
+pub struct TileMap {
+    pub origin: Vec2,
+    pub tile_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    pub tiles: Vec<Tile>,
+    // Texture and atlas region for each `Tile::texture` index.
+    pub textures: Vec<(Texture2D, Option<Rect>)>,
+}
+
+impl TileMap {
+    pub fn new(origin: Vec2, tile_size: f32, columns: usize, rows: usize) -> Self {
+        Self {
+            origin,
+            tile_size: tile_size.max(1.0),
+            columns,
+            rows,
+            tiles: vec![Tile::default(); columns * rows],
+            textures: Vec::new(),
+        }
+    }
+
+    // Registers a texture and returns the index tiles should use for it.
+    pub fn add_texture(&mut self, texture: Texture2D, source: Option<Rect>) -> u16 {
+        self.textures.push((texture, source));
+        (self.textures.len() - 1) as u16
+    }
+
+    pub fn get(&self, column: usize, row: usize) -> Tile {
+        if column < self.columns && row < self.rows {
+            self.tiles[row * self.columns + column]
+        } else {
+            Tile::default()
+        }
+    }
+
+    pub fn set(&mut self, column: usize, row: usize, tile: Tile) {
+        if column < self.columns && row < self.rows {
+            self.tiles[row * self.columns + column] = tile;
+        }
+    }
+
+    pub fn cell_at(&self, point: Vec2) -> Option<(usize, usize)> {
+        let local = (point - self.origin) / self.tile_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (column, row) = (local.x as usize, local.y as usize);
+        (column < self.columns && row < self.rows).then_some((column, row))
+    }
+
+    pub fn tile_rect(&self, column: usize, row: usize) -> Rect {
+        Rect::new(
+            self.origin.x + column as f32 * self.tile_size,
+            self.origin.y + row as f32 * self.tile_size,
+            self.tile_size,
+            self.tile_size,
+        )
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::new(
+            self.origin.x,
+            self.origin.y,
+            self.columns as f32 * self.tile_size,
+            self.rows as f32 * self.tile_size,
+        )
+    }
+
+    // Column and row ranges of the cells `area` touches, clamped to the map.
+    fn cell_range(&self, area: Rect) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        if self.columns == 0 || self.rows == 0 || !self.bounds().overlaps(&area) {
+            return (0..0, 0..0);
+        }
+        let cell = |v: f32, origin: f32, max: usize| {
+            (((v - origin) / self.tile_size).floor().max(0.0) as usize).min(max)
+        };
+        let columns = cell(area.x, self.origin.x, self.columns)
+            ..cell(area.right(), self.origin.x, self.columns - 1).saturating_add(1);
+        let rows = cell(area.y, self.origin.y, self.rows)
+            ..cell(area.bottom(), self.origin.y, self.rows - 1).saturating_add(1);
+        (columns, rows)
+    }
+
+    // Non-empty tiles whose cells overlap `area`.
+    pub fn tiles_in_rect(&self, area: Rect) -> Vec<(Rect, Tile)> {
+        let mut out = Vec::new();
+        let (columns, rows) = self.cell_range(area);
+        for row in rows {
+            for column in columns.clone() {
+                let tile = self.get(column, row);
+                if tile.kind != TileKind::Empty {
+                    out.push((self.tile_rect(column, row), tile));
+                }
+            }
+        }
+        out
+    }
+
+    // Draws the tiles inside `view`, one texture at a time so consecutive
+    // draws share a texture and batch together.
+    pub fn draw(&self, view: Rect) {
+        let (columns, rows) = self.cell_range(view);
+        for (index, (texture, source)) in self.textures.iter().enumerate() {
+            for row in rows.clone() {
+                for column in columns.clone() {
+                    let tile = self.get(column, row);
+                    if tile.kind == TileKind::Empty || tile.texture as usize != index {
+                        continue;
+                    }
+                    let rect = self.tile_rect(column, row);
+                    draw_texture_ex(
+                        texture,
+                        rect.x,
+                        rect.y,
+                        WHITE,
+                        DrawTextureParams {
+                            dest_size: Some(vec2(rect.w, rect.h)),
+                            source: *source,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Simulation steps at a fixed rate regardless of frame rate; drawing
+// interpolates between the last two steps.
+pub const DEFAULT_FIXED_DT: f32 = 1.0 / 120.0;
+// Longest frame the accumulator will absorb, so a stall (window drag,
+// breakpoint) doesn't trigger a burst of catch-up steps.
+const MAX_FRAME_DT: f32 = 0.25;
+// Broadphase cell size; a few sprites across so most boxes touch 1-4 cells.
+const SPATIAL_CELL_SIZE: f32 = 128.0;
+
 pub struct Scene {
     pub background: Option<Texture2D>,
+    pub background_source: Option<Rect>,
+    // Parallax layers, back to front; when empty `background` is drawn.
+    pub background_layers: Vec<BackgroundLayer>,
     pub entities: Vec<Entity>,
     pub platforms: Vec<Platform>,
+    pub tilemap: Option<TileMap>,
+    pub zones: Vec<Zone>,
     pub score: u32,
+    // Value of collectibles picked up; unlike `score` this excludes stomp
+    // rewards, so it can drive level progress.
+    pub collected: u32,
     pub move_speed: f32,
     pub jump_strength: f32,
     pub gravity: f32,
@@ -65,15 +727,42 @@ pub struct Scene {
     pub player_health: u32,
     pub player_max_health: u32,
     pub enemy_contact_damage: u32,
+    pub hazard_tile_damage: u32,
     pub hit_invincibility_duration: f32,
     pub hit_flash_enabled: bool,
+    pub stomp_enabled: bool,
+    pub stomp_bounce_strength: f32,
+    pub stomp_damage: u32,
+    pub stomp_score: u32,
+    pub enemy_death_duration: f32,
+    pub enemies_defeated: u32,
     pub hit_timer: f32,
     pub player_dead: bool,
     pub input: InputConfig,
+    pub movement: MovementConfig,
     pub world_width: f32,
     pub world_height: f32,
     pub time: f32,
     pub fall_respawn_offset: f32,
+    // Where the player's center goes on respawning: the start, or the last
+    // checkpoint touched.
+    pub respawn_point: Vec2,
+    pub active_checkpoint: Option<EntityId>,
+    // Set once the player touches the level exit.
+    pub goal_reached: bool,
+    // Deaths that respawn the player at `respawn_point` instead of ending
+    // the run.
+    pub respawns_left: u32,
+    // Health after a death respawn, capped at the max. The rules' 0 for
+    // the starting health is resolved when the scene is built.
+    pub respawn_health: u32,
+    // Health lost for falling out of the world.
+    pub fall_damage: u32,
+    // Score lost on every respawn, from a fall or a death.
+    pub respawn_score_penalty: u32,
+    pub power_ups: PowerUps,
+    pub power_up_config: PowerUpConfig,
+    pub lighting: LightingConfig,
     pub jump_sfx_volume: f32,
     pub hit_sfx_volume: f32,
     pub pickup_sfx_volume: f32,
@@ -92,17 +781,36 @@ pub struct Scene {
     pub enemy_shoot_interval: f32,
     pub enemy_shoot_timer: f32,
     pub enemy_shoot_range: f32,
-    pub projectile_speed: f32,
-    pub projectile_damage: u32,
+    pub projectiles: Vec<Projectile>,
+    pub player_shot: ProjectileSpec,
+    pub enemy_shot: ProjectileSpec,
+    pub player_fire_enabled: bool,
+    pub player_fire_interval: f32,
+    pub player_fire_timer: f32,
+    pub player_shot_score: u32,
     pub particles_enabled: bool,
     pub jump_particle_count: u32,
     pub hit_particle_count: u32,
     pub pickup_particle_count: u32,
     pub particles: Vec<Particle>,
-    pub enemy_behavior_mode: String,
-    pub enemy_chase_range: f32,
-    pub enemy_circle_radius: f32,
-    pub enemy_circle_speed: f32,
+    // Particle effects by name; see particles.rs.
+    pub effects: Effects,
+    pub emitters: Vec<Emitter>,
+    pub fixed_dt: f32,
+    pub accumulator: f32,
+    pub render_alpha: f32,
+    pending_input: InputFrame,
+    player_motion: PlayerMotion,
+    // Queued until taken with `take_events`.
+    events: Vec<GameEvent>,
+    platform_grid: SpatialGrid,
+    entity_grid: SpatialGrid,
+    registry: EntityRegistry,
+    // Applied at the end of the step they were queued in (or the next one).
+    pending_spawns: Vec<Entity>,
+    pending_despawns: Vec<EntityId>,
+    // Particle spread only; reseed with `seed_particles` for repeatable runs.
+    particle_rng: StdRng,
 }
 
 impl Scene {
@@ -144,19 +852,20 @@ impl Scene {
         jump_particle_count: u32,
         hit_particle_count: u32,
         pickup_particle_count: u32,
-        enemy_behavior_mode: String,
-        enemy_chase_range: f32,
-        enemy_circle_radius: f32,
-        enemy_circle_speed: f32,
     ) -> Self {
         let clamped_max = player_max_health.max(1);
         let clamped_start = player_start_health.max(1).min(clamped_max);
 
         Self {
             background: None,
+            background_source: None,
+            background_layers: Vec::new(),
             entities: Vec::new(),
             platforms: Vec::new(),
+            tilemap: None,
+            zones: Vec::new(),
             score: 0,
+            collected: 0,
             move_speed,
             jump_strength,
             gravity,
@@ -166,15 +875,33 @@ impl Scene {
             player_health: clamped_start,
             player_max_health: clamped_max,
             enemy_contact_damage,
+            hazard_tile_damage: 1,
             hit_invincibility_duration,
             hit_flash_enabled,
+            stomp_enabled: true,
+            stomp_bounce_strength: 320.0,
+            stomp_damage: 1,
+            stomp_score: 5,
+            enemy_death_duration: 0.4,
+            enemies_defeated: 0,
             hit_timer: 0.0,
             player_dead: false,
             input,
+            movement: MovementConfig::default(),
             world_width,
             world_height,
             time: 0.0,
             fall_respawn_offset,
+            respawn_point: vec2(world_width / 2.0, 0.0),
+            active_checkpoint: None,
+            goal_reached: false,
+            respawns_left: 0,
+            respawn_health: clamped_start,
+            fall_damage: 0,
+            respawn_score_penalty: 0,
+            power_ups: PowerUps::default(),
+            power_up_config: PowerUpConfig::default(),
+            lighting: LightingConfig::default(),
             jump_sfx_volume,
             hit_sfx_volume,
             pickup_sfx_volume,
@@ -193,32 +920,180 @@ impl Scene {
             enemy_shoot_interval,
             enemy_shoot_timer: 0.0,
             enemy_shoot_range,
-            projectile_speed,
-            projectile_damage,
+            projectiles: Vec::new(),
+            player_shot: ProjectileSpec::new(520.0, 1),
+            enemy_shot: ProjectileSpec::new(projectile_speed, projectile_damage),
+            player_fire_enabled: true,
+            player_fire_interval: 0.3,
+            player_fire_timer: 0.0,
+            player_shot_score: 5,
             particles_enabled,
             jump_particle_count,
             hit_particle_count,
             pickup_particle_count,
             particles: Vec::new(),
-            enemy_behavior_mode,
-            enemy_chase_range,
-            enemy_circle_radius,
-            enemy_circle_speed,
+            effects: builtin_effects(),
+            emitters: Vec::new(),
+            fixed_dt: DEFAULT_FIXED_DT,
+            accumulator: 0.0,
+            render_alpha: 1.0,
+            pending_input: InputFrame::default(),
+            player_motion: PlayerMotion::default(),
+            events: Vec::new(),
+            platform_grid: SpatialGrid::new(SPATIAL_CELL_SIZE),
+            entity_grid: SpatialGrid::new(SPATIAL_CELL_SIZE),
+            registry: EntityRegistry::default(),
+            pending_spawns: Vec::new(),
+            pending_despawns: Vec::new(),
+            particle_rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    // Advance by one rendered frame: runs as many fixed steps as have
+    // accumulated and returns how many ran. Input is polled once per frame;
+    // a jump press is held until a step consumes it, so it is neither lost
+    // on fast frames nor repeated across catch-up steps.
+    pub fn advance(
+        &mut self,
+        frame_dt: f32,
+        input: &mut dyn InputSource,
+    ) -> u32 {
+        let polled = input.poll(&self.input);
+        self.pending_input.left = polled.left;
+        self.pending_input.right = polled.right;
+        self.pending_input.jump |= polled.jump;
+        self.pending_input.jump_held = polled.jump_held;
+        self.pending_input.fire = polled.fire;
+        self.pending_input.dash |= polled.dash;
+        self.pending_input.up = polled.up;
+        self.pending_input.down = polled.down;
+
+        let fixed_dt = self.fixed_dt.max(1.0e-4);
+        self.accumulator += frame_dt.clamp(0.0, MAX_FRAME_DT);
+
+        let mut steps = 0;
+        while self.accumulator >= fixed_dt {
+            let frame = self.pending_input;
+            self.pending_input.jump = false;
+            self.pending_input.dash = false;
+            self.step(fixed_dt, frame);
+            self.accumulator -= fixed_dt;
+            steps += 1;
+            if self.player_dead {
+                self.accumulator = 0.0;
+                break;
+            }
         }
+
+        self.render_alpha = (self.accumulator / fixed_dt).clamp(0.0, 1.0);
+        steps
     }
 
-    pub fn update(&mut self, dt: f32, sounds: &Sounds, sfx_enabled: bool) {
+    // Run a single step of `dt` seconds with freshly polled input.
+    pub fn update(&mut self, dt: f32, input: &mut dyn InputSource) {
+        let frame = input.poll(&self.input);
+        self.step(dt, frame);
+        self.render_alpha = 1.0;
+    }
+
+    // Events queued by the steps run since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Adds an entity right away. For building scenes between steps; during
+    // a step use `spawn`.
+    pub fn add_entity(&mut self, mut entity: Entity) -> EntityId {
+        let id = self.registry.allocate();
+        entity.id = id;
+        self.registry.set_position(id, self.entities.len());
+        self.entities.push(entity);
+        id
+    }
+
+    // Queues an entity to be added at the end of the current step. The id
+    // is valid at once but only resolves after that.
+    pub fn spawn(&mut self, mut entity: Entity) -> EntityId {
+        let id = self.registry.allocate();
+        entity.id = id;
+        self.pending_spawns.push(entity);
+        id
+    }
+
+    // Queues an entity for removal at the end of the current step.
+    pub fn despawn(&mut self, id: EntityId) {
+        if self.registry.is_alive(id) && !self.pending_despawns.contains(&id) {
+            self.pending_despawns.push(id);
+        }
+    }
+
+    pub fn entity(&self, id: EntityId) -> Option<&Entity> {
+        self.registry.position(id).map(|i| &self.entities[i])
+    }
+
+    pub fn entity_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.registry.position(id).map(|i| &mut self.entities[i])
+    }
+
+    // Index into `entities`, valid until the end of the current step.
+    pub fn entity_index(&self, id: EntityId) -> Option<usize> {
+        self.registry.position(id)
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Entity> {
+        self.entities
+            .iter()
+            .find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn entities_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Entity> {
+        self.entities
+            .iter()
+            .filter(move |e| e.tag.as_deref() == Some(tag))
+    }
+
+    // Removes despawned entities and appends spawned ones, keeping the
+    // order of everything else.
+    fn apply_entity_queues(&mut self) {
+        if self.pending_despawns.is_empty() && self.pending_spawns.is_empty() {
+            return;
+        }
+        let despawns = std::mem::take(&mut self.pending_despawns);
+        self.pending_spawns.retain(|e| !despawns.contains(&e.id));
+        self.entities.retain(|e| !despawns.contains(&e.id));
+        for id in despawns {
+            self.registry.release(id);
+        }
+        self.entities.append(&mut self.pending_spawns);
+        for (i, entity) in self.entities.iter().enumerate() {
+            self.registry.set_position(entity.id, i);
+        }
+        self.rebuild_entity_grid();
+    }
+
+    fn step(&mut self, dt: f32, frame: InputFrame) {
+        for entity in &mut self.entities {
+            entity.prev_position = entity.position;
+        }
+        for platform in &mut self.platforms {
+            platform.prev_position = platform.position;
+        }
+
         self.time += dt;
 
-        // Update particles
+        // Update particles and run continuous emitters
         if self.particles_enabled {
             for p in &mut self.particles {
-                p.position += p.velocity * dt;
-                p.lifetime -= dt;
+                p.update(dt);
             }
             self.particles.retain(|p| p.lifetime > 0.0);
+            for emitter in &mut self.emitters {
+                emitter.update(dt, &mut self.particles, &mut self.particle_rng);
+            }
+            self.emitters.retain(|e| e.remaining > 0.0);
         } else {
             self.particles.clear();
+            self.emitters.clear();
         }
 
         if self.enemy_jump_timer > 0.0 {
@@ -229,6 +1104,10 @@ impl Scene {
             self.enemy_shoot_timer = (self.enemy_shoot_timer - dt).max(0.0);
         }
 
+        if self.player_fire_timer > 0.0 {
+            self.player_fire_timer = (self.player_fire_timer - dt).max(0.0);
+        }
+
         // Move platforms if enabled (per-platform)
         if self.moving_platform_enabled {
             let t = self.time * self.moving_platform_speed;
@@ -244,14 +1123,26 @@ impl Scene {
                 }
             }
         }
+        if dt > 0.0 {
+            for platform in &mut self.platforms {
+                platform.velocity = (platform.position - platform.prev_position) / dt;
+            }
+        }
 
         if self.hit_timer > 0.0 {
             self.hit_timer = (self.hit_timer - dt).max(0.0);
         }
 
+        self.rebuild_platform_grid();
+
         let mut any_enemy_jumped = false;
         let mut any_enemy_shot = false;
-        let platforms = &self.platforms;
+        let solids = Solids {
+            platforms: &self.platforms,
+            platform_grid: &self.platform_grid,
+            tilemap: self.tilemap.as_ref(),
+            scale: self.sprite_scale,
+        };
 
         let player_pos = self
             .entities
@@ -259,70 +1150,226 @@ impl Scene {
             .find(|e| matches!(e.kind, EntityKind::Player))
             .map(|e| e.position);
 
-        let mut new_projectiles: Vec<Entity> = Vec::new();
-        let mut new_particles: Vec<Particle> = Vec::new();
+        let mut new_projectiles: Vec<Projectile> = Vec::new();
+        let mut player_fired = false;
+        let mut player_fell = false;
+        let mut events: Vec<GameEvent> = Vec::new();
+
+        for kind in self.power_ups.tick(dt) {
+            events.push(GameEvent::PowerUpExpired { kind });
+        }
+        let config = self.power_up_config;
+        let speed_scale = if self.power_ups.is_active(PowerUpKind::Speed) {
+            config.speed_multiplier
+        } else {
+            1.0
+        };
+        let jump_scale = if self.power_ups.is_active(PowerUpKind::HighJump) {
+            config.jump_multiplier
+        } else {
+            1.0
+        };
+        let magnet = self.power_ups.is_active(PowerUpKind::Magnet);
 
         for entity in &mut self.entities {
             match entity.kind {
                 EntityKind::Player => {
                     let mut dir = 0.0;
-                    let left_down = is_key_down(self.input.move_left_primary)
-                        || self
-                            .input
-                            .move_left_alt
-                            .map_or(false, |k| is_key_down(k));
-                    let right_down = is_key_down(self.input.move_right_primary)
-                        || self
-                            .input
-                            .move_right_alt
-                            .map_or(false, |k| is_key_down(k));
-                    if left_down {
+                    if frame.left {
                         dir -= 1.0;
                     }
-                    if right_down {
+                    if frame.right {
                         dir += 1.0;
                     }
-                    entity.velocity.x = dir * self.move_speed;
+                    if dir != 0.0 {
+                        entity.facing = dir;
+                    }
+                    let m = self.movement;
+                    let motion = &mut self.player_motion;
+                    let carrier = solids.carry_rider(entity);
+                    let on_ground = solids.is_on_ground(entity);
+                    if on_ground {
+                        entity.momentum = Vec2::ZERO;
+                        motion.coyote = m.coyote_time;
+                        motion.air_jumps_left = m.air_jumps;
+                        motion.air_dash_used = false;
+                    } else {
+                        motion.coyote = (motion.coyote - dt).max(0.0);
+                    }
 
-                    let on_ground = is_on_ground(entity, platforms, self.sprite_scale);
+                    let body = entity_rect(entity, self.sprite_scale);
+                    let climb_zone = self
+                        .zones
+                        .iter()
+                        .find(|z| z.kind.climbable() && z.reaches(entity.position.x, &body))
+                        .copied();
+                    let in_water = self
+                        .zones
+                        .iter()
+                        .any(|z| z.kind == ZoneKind::Water && z.rect.contains(entity.position));
+                    let gravity_scale = zone_gravity_scale(&self.zones, entity.position);
+                    // Up grabs a ladder that goes on above the player's head, down
+                    // any; standing at its foot lets go
+                    match climb_zone {
+                        None => motion.climbing = false,
+                        Some(zone) if (frame.up && zone.rect.y < body.y) || frame.down => {
+                            motion.climbing = true
+                        }
+                        Some(_) if on_ground => motion.climbing = false,
+                        Some(_) => {}
+                    }
+                    if motion.climbing || in_water {
+                        entity.momentum = Vec2::ZERO;
+                        motion.air_jumps_left = m.air_jumps;
+                        motion.air_dash_used = false;
+                    }
+                    motion.jump_buffer = if frame.jump {
+                        m.jump_buffer.max(dt)
+                    } else {
+                        (motion.jump_buffer - dt).max(0.0)
+                    };
+                    motion.wall_lock = (motion.wall_lock - dt).max(0.0);
+                    motion.dash_timer = (motion.dash_timer - dt).max(0.0);
+                    motion.dash_cooldown = (motion.dash_cooldown - dt).max(0.0);
+
+                    // Side of the wall the player is against, only while airborne
+                    let wall = if on_ground {
+                        0.0
+                    } else if solids.touching_wall(entity, -1.0) {
+                        -1.0
+                    } else if solids.touching_wall(entity, 1.0) {
+                        1.0
+                    } else {
+                        0.0
+                    };
 
-                    let mut jump_pressed = false;
-                    if is_key_pressed(self.input.jump_primary) {
-                        jump_pressed = true;
+                    let mut jumped = false;
+                    if let Some(zone) = climb_zone.filter(|_| motion.climbing) {
+                        // Ladders are climbed off the top; vines end there
+                        let at_top = zone.kind == ZoneKind::Vine && body.y <= zone.rect.y;
+                        let up = frame.up && !at_top;
+                        let climb = f32::from(frame.down as u8) - f32::from(up as u8);
+                        entity.velocity = vec2(dir, climb) * m.climb_speed;
+                        motion.dash_timer = 0.0;
+                        motion.rising = false;
+                        // Up and jump may share a key, so only a jump
+                        // without up lets go
+                        if motion.jump_buffer > 0.0 && !frame.up {
+                            entity.velocity.y = -self.jump_strength * jump_scale;
+                            motion.climbing = false;
+                            jumped = true;
+                        }
+                    } else if motion.wall_lock <= 0.0 {
+                        let rate = match (on_ground, dir != 0.0) {
+                            (true, true) => m.acceleration,
+                            (true, false) => m.friction,
+                            (false, true) => m.air_acceleration,
+                            (false, false) => m.air_friction,
+                        };
+                        let swim_scale = if in_water { m.swim_speed_scale } else { 1.0 };
+                        let target = dir * self.move_speed * speed_scale * swim_scale;
+                        entity.velocity.x = if rate > 0.0 {
+                            approach(entity.velocity.x, target, rate * dt)
+                        } else {
+                            target
+                        };
                     }
-                    if self
-                        .input
-                        .jump_alt
-                        .map_or(false, |k| is_key_pressed(k))
-                    {
-                        jump_pressed = true;
-                    }
-
-                    if on_ground && jump_pressed {
-                        entity.velocity.y = -self.jump_strength;
-                        play_sound_opt(&sounds.jump, self.jump_sfx_volume, sfx_enabled);
-                        if self.particles_enabled {
-                            let foot_y = entity.position.y
-                                + entity.texture.height() * self.sprite_scale / 2.0;
-                            emit_particles(
-                                &mut new_particles,
-                                vec2(entity.position.x, foot_y),
-                                self.jump_particle_count,
-                                std::f32::consts::PI,
-                                std::f32::consts::TAU,
-                                80.0,
-                                0.35,
-                            );
+
+                    if motion.jump_buffer > 0.0 && !motion.climbing && !jumped {
+                        if in_water {
+                            entity.velocity.y = -m.swim_stroke_strength * jump_scale;
+                            jumped = true;
+                        } else if on_ground || motion.coyote > 0.0 {
+                            entity.velocity.y = -self.jump_strength * jump_scale;
+                            // Keep the platform's momentum. Only a rising platform
+                            // adds to the jump; a sinking one shouldn't swallow it.
+                            if let Some(platform_velocity) = carrier {
+                                entity.momentum.x = platform_velocity.x;
+                                entity.velocity.y += platform_velocity.y.min(0.0);
+                            }
+                            jumped = true;
+                        } else if m.wall_jump_enabled && wall != 0.0 {
+                            entity.velocity = vec2(-wall * m.wall_jump_push, -m.wall_jump_strength);
+                            entity.facing = -wall;
+                            motion.wall_lock = m.wall_jump_lock;
+                            jumped = true;
+                        } else if motion.air_jumps_left > 0 {
+                            entity.velocity.y =
+                                -self.jump_strength * jump_scale * m.air_jump_multiplier;
+                            motion.air_jumps_left -= 1;
+                            jumped = true;
                         }
                     }
 
-                    entity.velocity.y += self.gravity * dt;
+                    if jumped {
+                        motion.jump_buffer = 0.0;
+                        motion.coyote = 0.0;
+                        motion.dash_timer = 0.0;
+                        motion.rising = true;
+                        let foot_y = entity.position.y + entity.size.y * self.sprite_scale / 2.0;
+                        events.push(GameEvent::Jumped {
+                            position: vec2(entity.position.x, foot_y),
+                        });
+                    }
 
-                    entity.position += entity.velocity * dt;
+                    if motion.rising {
+                        if entity.velocity.y >= 0.0 {
+                            motion.rising = false;
+                        } else if m.variable_jump && !(frame.jump_held || frame.jump) {
+                            entity.velocity.y *= m.jump_cut.clamp(0.0, 1.0);
+                            motion.rising = false;
+                        }
+                    }
 
-                    resolve_platform_collisions(entity, platforms, self.sprite_scale);
+                    if frame.dash
+                        && m.dash_enabled
+                        && !motion.climbing
+                        && motion.dash_cooldown <= 0.0
+                        && (on_ground || !motion.air_dash_used)
+                    {
+                        motion.dash_timer = m.dash_duration;
+                        motion.dash_cooldown = m.dash_cooldown;
+                        motion.dash_dir = entity.facing;
+                        motion.air_dash_used = !on_ground;
+                        motion.rising = false;
+                    }
+
+                    // Climbing and dashes ignore gravity; wall slides cap the
+                    // fall speed, water slows it
+                    if motion.climbing {
+                        // Already set from the climb input
+                    } else if motion.dash_timer > 0.0 {
+                        entity.velocity = vec2(motion.dash_dir * m.dash_speed, 0.0);
+                    } else if in_water {
+                        entity.velocity.y +=
+                            self.gravity * gravity_scale * (1.0 - m.water_buoyancy) * dt;
+                        entity.velocity.y *= (1.0 - m.water_drag * dt).max(0.0);
+                    } else {
+                        entity.velocity.y += self.gravity * gravity_scale * dt;
+                        if m.wall_slide_enabled && wall != 0.0 && dir == wall {
+                            entity.velocity.y = entity.velocity.y.min(m.wall_slide_speed);
+                        }
+                    }
 
-                    let half_w = entity.texture.width() * self.sprite_scale / 2.0;
+                    // Climbing down passes one-way platforms above the
+                    // ladder's foot
+                    let floor = match climb_zone {
+                        Some(zone) if motion.climbing => zone.rect.bottom(),
+                        _ => f32::NEG_INFINITY,
+                    };
+                    let hits = solids.move_through(
+                        entity,
+                        (entity.velocity + entity.momentum) * dt,
+                        floor,
+                    );
+                    if hits.left || hits.right || hits.ground {
+                        entity.momentum = Vec2::ZERO;
+                    }
+                    if hits.left || hits.right {
+                        motion.dash_timer = 0.0;
+                    }
+
+                    let half_w = entity.size.x * self.sprite_scale / 2.0;
                     if entity.position.x - half_w < 0.0 {
                         entity.position.x = half_w;
                     }
@@ -330,65 +1377,164 @@ impl Scene {
                         entity.position.x = self.world_width - half_w;
                     }
 
-                    let half_h = entity.texture.height() * self.sprite_scale / 2.0;
+                    if frame.fire && self.player_fire_enabled && self.player_fire_timer <= 0.0 {
+                        let muzzle = entity.position
+                            + vec2(entity.facing * entity.size.x * self.sprite_scale / 2.0, 0.0);
+                        new_projectiles.push(Projectile {
+                            owner: Some(entity.id),
+                            ..self.player_shot.spawn(Team::Player, muzzle, vec2(entity.facing, 0.0))
+                        });
+                        events.push(GameEvent::Fired {
+                            position: muzzle,
+                            team: Team::Player,
+                        });
+                        player_fired = true;
+                    }
+
+                    let half_h = entity.size.y * self.sprite_scale / 2.0;
                     if entity.position.y - half_h > self.world_height + self.fall_respawn_offset {
-                        entity.position = vec2(self.world_width / 2.0, 0.0);
-                        entity.velocity = Vec2::ZERO;
+                        player_fell = true;
                     }
                 }
                 EntityKind::Enemy => {
-                    entity.velocity.y += self.gravity * self.enemy_gravity_scale * dt;
-
-                    // Horizontal behavior: patrol / chase / circle
-                    match self.enemy_behavior_mode.to_lowercase().as_str() {
-                        "chase" => {
-                            if let Some(player_pos) = player_pos {
-                                let dx = player_pos.x - entity.position.x;
-                                if dx.abs() <= self.enemy_chase_range {
-                                    let dir = if dx > 0.0 { 1.0 } else { -1.0 };
-                                    entity.velocity.x = dir * self.enemy_speed;
+                    if let Some(remaining) = entity.dying {
+                        entity.dying = Some(remaining - dt);
+                        continue;
+                    }
+                    let free_flying = matches!(
+                        entity.behavior,
+                        EnemyBehavior::Circle { .. } | EnemyBehavior::Flyer { .. }
+                    );
+                    let carrier = if free_flying {
+                        None
+                    } else {
+                        solids.carry_rider(entity)
+                    };
+                    if !free_flying {
+                        // Edge patrollers and hoppers only make sense on the
+                        // ground, so they always fall.
+                        let gravity_scale = match entity.behavior {
+                            EnemyBehavior::EdgePatrol | EnemyBehavior::Hopper { .. } => {
+                                self.enemy_gravity_scale.max(1.0)
+                            }
+                            _ => self.enemy_gravity_scale,
+                        };
+                        entity.velocity.y += self.gravity
+                            * gravity_scale
+                            * zone_gravity_scale(&self.zones, entity.position)
+                            * dt;
+                    }
+                    if entity.cooldown > 0.0 {
+                        entity.cooldown = (entity.cooldown - dt).max(0.0);
+                    }
+                    if entity.hurt_timer > 0.0 {
+                        entity.hurt_timer = (entity.hurt_timer - dt).max(0.0);
+                    }
+                    let to_player = player_pos.map(|p| p - entity.position);
+
+                    // Horizontal intent; velocity.x carries over between steps
+                    // for behaviours that just keep walking.
+                    let mut hover_dy = 0.0;
+                    let behavior = entity.behavior;
+                    match &behavior {
+                        EnemyBehavior::Patrol => {}
+                        EnemyBehavior::EdgePatrol => {
+                            if entity.velocity.x != 0.0
+                                && solids.is_on_ground(entity)
+                                && !solids.has_ground_ahead(entity, entity.velocity.x)
+                            {
+                                entity.velocity.x = -entity.velocity.x;
+                            }
+                        }
+                        EnemyBehavior::Chase { range } => {
+                            if let Some(d) = to_player {
+                                if d.x.abs() <= *range {
+                                    entity.velocity.x = d.x.signum() * self.enemy_speed;
                                 }
                             }
                         }
-                        "circle" => {
+                        EnemyBehavior::Circle { radius, speed } => {
                             // Simple circular motion around base_position
-                            let angle = self.time * self.enemy_circle_speed + entity.phase;
-                            let r = self.enemy_circle_radius;
-                            entity.position.x =
-                                entity.base_position.x + angle.cos() * r;
-                            entity.position.y =
-                                entity.base_position.y + angle.sin() * r;
+                            let angle = self.time * *speed + entity.phase;
+                            entity.position.x = entity.base_position.x + angle.cos() * *radius;
+                            entity.position.y = entity.base_position.y + angle.sin() * *radius;
                         }
-                        _ => {
-                            // "patrol" or unknown: current velocity.x is used
+                        EnemyBehavior::Flyer { amplitude, speed } => {
+                            let angle = self.time * *speed + entity.phase;
+                            let target_y = entity.base_position.y + angle.sin() * *amplitude;
+                            hover_dy = target_y - entity.position.y;
+                            entity.velocity.y = 0.0;
+                        }
+                        EnemyBehavior::Turret => {
+                            entity.velocity.x = 0.0;
+                        }
+                        EnemyBehavior::Hopper { range, interval } => {
+                            if solids.is_on_ground(entity) {
+                                entity.velocity.x = 0.0;
+                                if let Some(d) = to_player {
+                                    if entity.cooldown <= 0.0 && d.length() <= *range {
+                                        entity.velocity.x = d.x.signum() * self.enemy_speed;
+                                        entity.velocity.y = -self.enemy_jump_strength;
+                                        entity.cooldown = *interval;
+                                        any_enemy_jumped = true;
+                                    }
+                                }
+                            }
+                        }
+                        EnemyBehavior::Ambusher { range } => {
+                            if !entity.awake {
+                                entity.velocity.x = 0.0;
+                                entity.awake = to_player.is_some_and(|d| d.length() <= *range);
+                            }
+                            if entity.awake {
+                                if let Some(d) = to_player {
+                                    entity.velocity.x = d.x.signum() * self.enemy_speed;
+                                }
+                            }
                         }
                     }
 
-                    if !matches!(self.enemy_behavior_mode.to_lowercase().as_str(), "circle") {
-                        entity.position += entity.velocity * dt;
-                    }
-
-                    resolve_platform_collisions(entity, platforms, self.sprite_scale);
+                    if !matches!(entity.behavior, EnemyBehavior::Circle { .. }) {
+                        let vx = entity.velocity.x;
+                        let delta = (entity.velocity + entity.momentum) * dt + vec2(0.0, hover_dy);
+                        let hits = solids.move_and_collide(entity, delta);
+                        // Turn around when walking into a wall
+                        if hits.left || hits.right {
+                            entity.velocity.x = -vx;
+                            entity.momentum = Vec2::ZERO;
+                        }
+                        if hits.ground {
+                            entity.momentum = Vec2::ZERO;
+                        }
 
-                    let half_w = entity.texture.width() * self.sprite_scale / 2.0;
-                    if entity.position.x - half_w < 0.0 || entity.position.x + half_w > self.world_width {
-                        if !matches!(self.enemy_behavior_mode.to_lowercase().as_str(), "circle") {
-                            entity.velocity.x = -entity.velocity.x;
+                        let half_w = entity.size.x * self.sprite_scale / 2.0;
+                        if entity.position.x - half_w < 0.0 {
+                            entity.velocity.x = entity.velocity.x.abs();
+                        } else if entity.position.x + half_w > self.world_width {
+                            entity.velocity.x = -entity.velocity.x.abs();
                         }
                     }
 
                     if self.enemy_jump_enabled
                         && entity.jumping
+                        && !free_flying
                         && self.enemy_jump_interval > 0.0
                         && self.enemy_jump_timer <= 0.0
                     {
-                        if is_on_ground(entity, platforms, self.sprite_scale) {
+                        if solids.is_on_ground(entity) {
                             entity.velocity.y = -self.enemy_jump_strength;
+                            if let Some(platform_velocity) = carrier {
+                                entity.momentum.x = platform_velocity.x;
+                                entity.velocity.y += platform_velocity.y.min(0.0);
+                            }
                             any_enemy_jumped = true;
                         }
                     }
 
-                    if self.enemy_shoot_enabled
+                    // Turrets always shoot; other enemies only when enabled
+                    let shoots = self.enemy_shoot_enabled
+                        || matches!(entity.behavior, EnemyBehavior::Turret);
+                    if shoots
                         && self.enemy_shoot_interval > 0.0
                         && self.enemy_shoot_timer <= 0.0
                     {
@@ -396,18 +1542,17 @@ impl Scene {
                             let to_player = player_pos - entity.position;
                             let dist = to_player.length();
                             if dist > 0.0 && dist <= self.enemy_shoot_range {
-                                let dir = to_player / dist;
-                                let vel = dir * self.projectile_speed;
-                                new_projectiles.push(Entity {
-                                    kind: EntityKind::Projectile,
-                                    texture: entity.texture.clone(),
+                                new_projectiles.push(Projectile {
+                                    owner: Some(entity.id),
+                                    ..self.enemy_shot.spawn(
+                                        Team::Enemy,
+                                        entity.position,
+                                        to_player,
+                                    )
+                                });
+                                events.push(GameEvent::Fired {
                                     position: entity.position,
-                                    velocity: vel,
-                                    value: 0,
-                                    health_value: 0,
-                                    base_position: entity.position,
-                                    phase: 0.0,
-                                    jumping: false,
+                                    team: Team::Enemy,
                                 });
                                 any_enemy_shot = true;
                             }
@@ -415,15 +1560,35 @@ impl Scene {
                     }
                 }
                 EntityKind::Collectible => {
+                    // The magnet drags the resting point, so bobbing carries on
+                    if let Some(target) = player_pos.filter(|_| magnet) {
+                        let to_player = target - entity.base_position;
+                        let distance = to_player.length();
+                        if distance > 1.0 && distance <= config.magnet_radius {
+                            let step = (config.magnet_speed * dt).min(distance);
+                            entity.base_position += to_player / distance * step;
+                            entity.position = entity.base_position;
+                        }
+                    }
                     if self.collectible_bob_enabled {
                         let angle = self.time * self.collectible_bob_speed + entity.phase;
                         let offset = angle.sin() * self.collectible_bob_amplitude;
                         entity.position.y = entity.base_position.y + offset;
                     }
                 }
-                EntityKind::Projectile => {
-                    entity.position += entity.velocity * dt;
-                }
+                EntityKind::Hazard => match entity.hazard.motion {
+                    HazardMotion::Static => {}
+                    HazardMotion::Line { dx, dy, speed } => {
+                        let s = (self.time * speed + entity.phase).sin();
+                        entity.position = entity.base_position + vec2(dx, dy) * s;
+                    }
+                    HazardMotion::Circle { radius, speed } => {
+                        let angle = self.time * speed + entity.phase;
+                        entity.position =
+                            entity.base_position + vec2(angle.cos(), angle.sin()) * radius;
+                    }
+                },
+                EntityKind::Checkpoint | EntityKind::Goal => {}
             }
         }
 
@@ -435,25 +1600,144 @@ impl Scene {
             self.enemy_shoot_timer = self.enemy_shoot_interval;
         }
 
-        // Add newly spawned projectiles
-        if !new_projectiles.is_empty() {
-            self.entities.extend(new_projectiles);
+        if player_fired {
+            self.player_fire_timer = self.player_fire_interval;
+        }
+
+        // Pick each entity's animation from what it is doing
+        for entity in &mut self.entities {
+            if matches!(entity.kind, EntityKind::Enemy) && entity.velocity.x != 0.0 {
+                entity.facing = entity.velocity.x.signum();
+            }
+            if entity.animator.is_none() {
+                continue;
+            }
+            let free_flying = matches!(
+                entity.behavior,
+                EnemyBehavior::Circle { .. } | EnemyBehavior::Flyer { .. }
+            );
+            let airborne = match entity.kind {
+                EntityKind::Player => !solids.is_on_ground(entity),
+                EntityKind::Enemy => !free_flying && !solids.is_on_ground(entity),
+                EntityKind::Collectible
+                | EntityKind::Hazard
+                | EntityKind::Checkpoint
+                | EntityKind::Goal => false,
+            };
+            let hurt = match entity.kind {
+                EntityKind::Player => self.hit_timer > 0.0,
+                _ => entity.hurt_timer > 0.0,
+            };
+            let state =
+                AnimState::pick(entity.dying.is_some(), hurt, airborne, entity.velocity);
+            if let Some(animator) = entity.animator.as_mut() {
+                animator.update(state, dt);
+            }
+        }
+
+        // Move projectiles; they expire when their lifetime runs out or
+        // they hit something solid
+        self.projectiles.extend(new_projectiles);
+        for p in &mut self.projectiles {
+            p.prev_position = p.position;
+            p.velocity.y += self.gravity * p.gravity_scale * dt;
+            p.position += p.velocity * dt;
+            p.lifetime -= dt;
+            if p.collides {
+                let rect = p.rect(self.sprite_scale);
+                let blocked = solids
+                    .colliders_in(rect)
+                    .iter()
+                    .any(|c| !c.one_way && c.rect.overlaps(&rect));
+                if blocked {
+                    p.lifetime = 0.0;
+                }
+            }
         }
 
-        // Cull projectiles that leave the world bounds
+        // Cull projectiles that expired or left the world bounds, and enemies
+        // whose death effect has finished
         let world_w = self.world_width;
         let world_h = self.world_height;
-        self.entities.retain(|e| {
-            if matches!(e.kind, EntityKind::Projectile) {
-                let pos = e.position;
-                pos.x >= -32.0
-                    && pos.x <= world_w + 32.0
-                    && pos.y >= -32.0
-                    && pos.y <= world_h + 32.0
+        self.projectiles.retain(|p| {
+            let pos = p.position;
+            p.lifetime > 0.0
+                && pos.x >= -32.0
+                && pos.x <= world_w + 32.0
+                && pos.y >= -32.0
+                && pos.y <= world_h + 32.0
+        });
+        let finished: Vec<EntityId> = self
+            .entities
+            .iter()
+            .filter(|e| matches!(e.kind, EntityKind::Enemy) && e.dying.is_some_and(|t| t <= 0.0))
+            .map(|e| e.id)
+            .collect();
+        for id in finished {
+            self.despawn(id);
+        }
+
+        self.rebuild_entity_grid();
+
+        let damage_scale = if self.power_ups.is_active(PowerUpKind::DoubleDamage) {
+            config.damage_multiplier.max(1)
+        } else {
+            1
+        };
+
+        // Player shots hurt the enemies they pass through
+        let mut spent: Vec<usize> = Vec::new();
+        for pi in 0..self.projectiles.len() {
+            if self.projectiles[pi].team != Team::Player {
+                continue;
+            }
+            let rect = self.projectiles[pi].rect(self.sprite_scale);
+            for i in self.entities_in_rect(rect) {
+                let e = &self.entities[i];
+                if !matches!(e.kind, EntityKind::Enemy)
+                    || e.dying.is_some()
+                    || e.hurt_timer > 0.0
+                    || !entity_rect(e, self.sprite_scale).overlaps(&rect)
+                {
+                    continue;
+                }
+                let damage = self.projectiles[pi].damage.saturating_mul(damage_scale);
+                self.damage_enemy(i, damage, self.player_shot_score, &mut events);
+                let p = &mut self.projectiles[pi];
+                if p.pierce == 0 {
+                    spent.push(pi);
+                    break;
+                }
+                p.pierce -= 1;
+            }
+        }
+        if !spent.is_empty() {
+            let mut index = 0;
+            self.projectiles.retain(|_| {
+                let keep = !spent.contains(&index);
+                index += 1;
+                keep
+            });
+        }
+
+        // Falling out of the world costs `fall_damage` and respawns the player
+        if player_fell {
+            let position = self.player_position().unwrap_or(self.respawn_point);
+            let damage = self.fall_damage.min(self.player_health);
+            if damage > 0 {
+                self.player_health -= damage;
+                events.push(GameEvent::Damaged {
+                    position,
+                    amount: damage,
+                    health: self.player_health,
+                });
+            }
+            if self.player_health == 0 {
+                self.kill_player(position, &mut events);
             } else {
-                true
+                self.respawn_player(&mut events);
             }
-        });
+        }
 
         // Player bounding box after movement
         let player_rect = self
@@ -462,80 +1746,178 @@ impl Scene {
             .find(|e| matches!(e.kind, EntityKind::Player))
             .map(|e| entity_rect(e, self.sprite_scale));
 
-        // Take damage when touching enemies or projectiles
+        // Entities near the player, from the broadphase
+        let touching = player_rect
+            .map(|rect| self.entities_in_rect(rect))
+            .unwrap_or_default();
+
+        // Stomp enemies the player falls onto from above
+        let mut stomped: Vec<usize> = Vec::new();
+        let player_index = self
+            .entities
+            .iter()
+            .position(|e| matches!(e.kind, EntityKind::Player));
+        if let (Some(pi), Some(rect)) = (player_index, player_rect) {
+            let player = &self.entities[pi];
+            if self.stomp_enabled && self.player_health > 0 && player.velocity.y > 0.0 {
+                let prev_bottom =
+                    player.prev_position.y + player.size.y * self.sprite_scale / 2.0;
+                for &i in &touching {
+                    let e = &self.entities[i];
+                    if !matches!(e.kind, EntityKind::Enemy) || e.dying.is_some() {
+                        continue;
+                    }
+                    let prev_top = e.prev_position.y.min(e.position.y)
+                        - e.size.y * self.sprite_scale / 2.0;
+                    if entity_rect(e, self.sprite_scale).overlaps(&rect)
+                        && prev_bottom <= prev_top + STOMP_TOLERANCE
+                    {
+                        stomped.push(i);
+                    }
+                }
+            }
+        }
+        if let Some(pi) = player_index.filter(|_| !stomped.is_empty()) {
+            for &i in &stomped {
+                let damage = self.stomp_damage.saturating_mul(damage_scale);
+                self.damage_enemy(i, damage, self.stomp_score, &mut events);
+            }
+            let player = &mut self.entities[pi];
+            player.velocity.y = -self.stomp_bounce_strength;
+            player.momentum = Vec2::ZERO;
+            events.push(GameEvent::Stomped {
+                position: player.position,
+            });
+        }
+
+        // Take damage when touching enemies, enemy projectiles or hazards
         if let Some(ref rect) = player_rect {
             if self.player_health > 0 {
                 let mut hit_enemy = false;
-                let mut hit_projectile = false;
+                let mut projectile_damage: u32 = 0;
+                let mut hit_hazard = false;
+                let mut hazard_damage: u32 = 0;
+                let mut lethal = false;
+
+                for &i in &touching {
+                    let e = &self.entities[i];
+                    if matches!(e.kind, EntityKind::Hazard)
+                        && entity_rect(e, self.sprite_scale).overlaps(rect)
+                    {
+                        lethal |= e.hazard.lethal;
+                        hazard_damage = hazard_damage.max(e.hazard.damage.max(1));
+                    }
+                }
 
                 if self.hit_timer <= 0.0 {
-                    for e in &self.entities {
-                        match e.kind {
-                            EntityKind::Enemy => {
-                                let enemy_rect = entity_rect(e, self.sprite_scale);
-                                if enemy_rect.overlaps(rect) {
-                                    hit_enemy = true;
-                                }
-                            }
-                            EntityKind::Projectile => {
-                                let proj_rect = entity_rect(e, self.sprite_scale);
-                                if proj_rect.overlaps(rect) {
-                                    hit_projectile = true;
-                                }
-                            }
-                            _ => {}
-                        }
-                        if hit_enemy || hit_projectile {
-                            break;
-                        }
+                    if let Some(map) = &self.tilemap {
+                        hit_hazard = map
+                            .tiles_in_rect(*rect)
+                            .iter()
+                            .any(|(r, t)| t.kind == TileKind::Hazard && r.overlaps(rect));
                     }
+                    hit_enemy = touching.iter().any(|&i| {
+                        let e = &self.entities[i];
+                        matches!(e.kind, EntityKind::Enemy)
+                            && e.dying.is_none()
+                            && !stomped.contains(&i)
+                            && entity_rect(e, self.sprite_scale).overlaps(rect)
+                    });
+                    projectile_damage = self
+                        .projectiles
+                        .iter()
+                        .filter(|p| p.team == Team::Enemy && p.rect(self.sprite_scale).overlaps(rect))
+                        .map(|p| p.damage)
+                        .max()
+                        .unwrap_or(0);
                 }
 
-                if self.hit_timer <= 0.0 && (hit_enemy || hit_projectile) {
+                let hit_projectile = projectile_damage > 0;
+                let hit_object = hazard_damage > 0;
+                let invincible = self.power_ups.is_active(PowerUpKind::Invincibility);
+                let hit = !invincible
+                    && (lethal
+                        || (self.hit_timer <= 0.0
+                            && (hit_enemy || hit_projectile || hit_hazard || hit_object)));
+                let center = vec2(rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+                // Shields take ordinary hits; lethal hazards go straight through
+                if hit && !lethal && self.power_ups.use_shield() {
+                    self.hit_timer = self.hit_invincibility_duration;
+                    events.push(GameEvent::ShieldAbsorbed { position: center });
+                    if !self.power_ups.is_active(PowerUpKind::Shield) {
+                        events.push(GameEvent::PowerUpExpired {
+                            kind: PowerUpKind::Shield,
+                        });
+                    }
+                } else if hit {
                     self.hit_timer = self.hit_invincibility_duration;
                     let mut damage: u32 = 0;
                     if hit_enemy {
                         damage = damage.saturating_add(self.enemy_contact_damage.max(1));
                     }
                     if hit_projectile {
-                        damage = damage.saturating_add(self.projectile_damage.max(1));
+                        damage = damage.saturating_add(projectile_damage);
                     }
-                    let damage = damage.max(1);
-
-                    if damage >= self.player_health {
-                        self.player_health = 0;
-                        self.player_dead = true;
+                    if hit_hazard {
+                        damage = damage.saturating_add(self.hazard_tile_damage.max(1));
+                    }
+                    if hit_object {
+                        damage = damage.saturating_add(hazard_damage);
+                    }
+                    let damage = if lethal {
+                        self.player_health
                     } else {
-                        self.player_health -= damage;
-                    }
-                    play_sound_opt(&sounds.hit, self.hit_sfx_volume, sfx_enabled);
-
-                    if self.particles_enabled {
-                        let center = vec2(
-                            rect.x + rect.w / 2.0,
-                            rect.y + rect.h / 2.0,
-                        );
-                        emit_particles(
-                            &mut new_particles,
-                            center,
-                            self.hit_particle_count,
-                            0.0,
-                            std::f32::consts::TAU,
-                            90.0,
-                            0.5,
-                        );
+                        damage.max(1)
+                    };
+
+                    let amount = damage.min(self.player_health);
+                    self.player_health -= amount;
+                    events.push(GameEvent::Damaged {
+                        position: center,
+                        amount,
+                        health: self.player_health,
+                    });
+                    if self.player_health == 0 {
+                        self.kill_player(center, &mut events);
                     }
                 }
 
                 // Remove projectiles that hit the player, even when invincible
-                self.entities.retain(|e| {
-                    if matches!(e.kind, EntityKind::Projectile) {
-                        let r = entity_rect(e, self.sprite_scale);
-                        if r.overlaps(rect) {
-                            return false;
-                        }
-                    }
-                    true
+                let scale = self.sprite_scale;
+                self.projectiles
+                    .retain(|p| p.team != Team::Enemy || !p.rect(scale).overlaps(rect));
+            }
+        }
+
+        // Touching a checkpoint moves the respawn point there, feet on its
+        // base
+        if let Some(player_rect) = player_rect.filter(|_| !self.player_dead) {
+            for &i in &touching {
+                let e = &self.entities[i];
+                if !matches!(e.kind, EntityKind::Checkpoint)
+                    || self.active_checkpoint == Some(e.id)
+                    || !entity_rect(e, self.sprite_scale).overlaps(&player_rect)
+                {
+                    continue;
+                }
+                let base = e.position.y + e.size.y * self.sprite_scale / 2.0;
+                let position = vec2(e.position.x, base - player_rect.h / 2.0);
+                self.active_checkpoint = Some(e.id);
+                self.respawn_point = position;
+                events.push(GameEvent::CheckpointReached { position });
+            }
+        }
+
+        // Reaching the exit completes the level, once
+        if let Some(player_rect) = player_rect.filter(|_| !self.player_dead && !self.goal_reached) {
+            let exit = touching.iter().map(|&i| &self.entities[i]).find(|e| {
+                matches!(e.kind, EntityKind::Goal)
+                    && entity_rect(e, self.sprite_scale).overlaps(&player_rect)
+            });
+            if let Some(e) = exit {
+                self.goal_reached = true;
+                events.push(GameEvent::GoalReached {
+                    position: e.position,
                 });
             }
         }
@@ -544,23 +1926,31 @@ impl Scene {
         if let Some(player_rect) = player_rect {
             let mut collected_value: u32 = 0;
             let mut collected_health: u32 = 0;
-            let mut pickup_bursts: Vec<Vec2> = Vec::new();
-            self.entities.retain(|e| {
-                if matches!(e.kind, EntityKind::Collectible) {
-                    let r = entity_rect(e, self.sprite_scale);
-                    if r.overlaps(&player_rect) {
-                        collected_value = collected_value.saturating_add(e.value);
-                        collected_health = collected_health.saturating_add(e.health_value);
-                        play_sound_opt(&sounds.pickup, self.pickup_sfx_volume, sfx_enabled);
-                        if self.particles_enabled {
-                            pickup_bursts.push(e.position);
-                        }
-                        return false;
+            for &i in &touching {
+                let e = &self.entities[i];
+                if matches!(e.kind, EntityKind::Collectible)
+                    && entity_rect(e, self.sprite_scale).overlaps(&player_rect)
+                {
+                    collected_value = collected_value.saturating_add(e.value);
+                    collected_health = collected_health.saturating_add(e.health_value);
+                    events.push(GameEvent::Collected {
+                        position: e.position,
+                        value: e.value,
+                        health: e.health_value,
+                    });
+                    if let Some(power_up) = e.power_up {
+                        events.push(GameEvent::PowerUpCollected {
+                            position: e.position,
+                            kind: power_up.kind,
+                        });
+                        self.power_ups.apply(power_up, &self.power_up_config);
                     }
+                    let id = e.id;
+                    self.despawn(id);
                 }
-                true
-            });
+            }
             self.score = self.score.saturating_add(collected_value);
+            self.collected = self.collected.saturating_add(collected_value);
             if collected_health > 0 && self.player_health > 0 {
                 let new_health = self
                     .player_health
@@ -568,27 +1958,142 @@ impl Scene {
                     .min(self.player_max_health);
                 self.player_health = new_health;
             }
+        }
 
-            if self.particles_enabled {
-                for pos in pickup_bursts {
-                    emit_particles(
-                        &mut new_particles,
-                        pos,
-                        self.pickup_particle_count,
-                        0.0,
-                        std::f32::consts::TAU,
-                        60.0,
-                        0.4,
-                    );
+        self.apply_entity_queues();
+
+        self.emit_event_particles(&events);
+        self.events.extend(events);
+    }
+
+    // Particle effects for this step's jumps, hits and pickups.
+    fn emit_event_particles(&mut self, events: &[GameEvent]) {
+        if !self.particles_enabled {
+            return;
+        }
+        for event in events {
+            match *event {
+                GameEvent::Jumped { position } => {
+                    self.emit_effect("jump", position, self.jump_particle_count)
+                }
+                GameEvent::Damaged { position, .. } | GameEvent::EnemyKilled { position, .. } => {
+                    self.emit_effect("hit", position, self.hit_particle_count)
+                }
+                GameEvent::Collected { position, .. }
+                | GameEvent::CheckpointReached { position }
+                | GameEvent::GoalReached { position } => {
+                    self.emit_effect("pickup", position, self.pickup_particle_count)
                 }
+                _ => {}
             }
         }
+    }
 
-        if self.particles_enabled && !new_particles.is_empty() {
-            self.particles.extend(new_particles);
+    // Starts the named effect at `position`. Bursts that don't set their
+    // own count spawn `default_count` particles.
+    pub fn emit_effect(&mut self, name: &str, position: Vec2, default_count: u32) {
+        if !self.particles_enabled {
+            return;
+        }
+        let Some(effect) = self.effects.get(name).cloned() else {
+            return;
+        };
+        match effect.def.mode {
+            EmitterMode::Burst => {
+                let count = match effect.def.count {
+                    0 => default_count,
+                    count => count,
+                };
+                emit(&mut self.particles, &effect, position, count, &mut self.particle_rng);
+            }
+            EmitterMode::Continuous => self.emitters.push(Emitter::new(effect, position)),
         }
     }
 
+    pub fn seed_particles(&mut self, seed: u64) {
+        self.particle_rng = StdRng::seed_from_u64(seed);
+    }
+
+    // Out of health: respawns the player if any respawns are left, otherwise
+    // ends the run.
+    fn kill_player(&mut self, position: Vec2, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::Died { position });
+        if self.respawns_left == 0 {
+            self.player_health = 0;
+            self.player_dead = true;
+            return;
+        }
+        self.respawns_left -= 1;
+        self.player_health = self.respawn_health.min(self.player_max_health).max(1);
+        self.power_ups.clear();
+        self.respawn_player(events);
+    }
+
+    // Puts the player back at `respawn_point`, at rest and briefly
+    // invincible, and takes the respawn score penalty.
+    fn respawn_player(&mut self, events: &mut Vec<GameEvent>) {
+        let position = self.respawn_point;
+        let Some(player) = self
+            .entities
+            .iter_mut()
+            .find(|e| matches!(e.kind, EntityKind::Player))
+        else {
+            return;
+        };
+        player.position = position;
+        player.prev_position = position;
+        player.velocity = Vec2::ZERO;
+        player.momentum = Vec2::ZERO;
+        self.player_motion = PlayerMotion::default();
+        self.hit_timer = self.hit_invincibility_duration;
+        let score_penalty = self.respawn_score_penalty.min(self.score);
+        self.score -= score_penalty;
+        events.push(GameEvent::Respawned {
+            position,
+            score_penalty,
+        });
+    }
+
+    // Applies `damage` to the enemy at `index`. At zero health it starts its
+    // death effect and awards `score`.
+    fn damage_enemy(&mut self, index: usize, damage: u32, score: u32, events: &mut Vec<GameEvent>) {
+        let enemy = &mut self.entities[index];
+        enemy.health = enemy.health.saturating_sub(damage.max(1));
+        enemy.hurt_timer = ENEMY_HURT_DURATION;
+        if enemy.health > 0 {
+            return;
+        }
+        enemy.dying = Some(self.enemy_death_duration.max(0.0));
+        enemy.velocity = Vec2::ZERO;
+        self.score = self.score.saturating_add(score);
+        self.enemies_defeated = self.enemies_defeated.saturating_add(1);
+        events.push(GameEvent::EnemyKilled {
+            position: enemy.position,
+            score,
+        });
+    }
+
+    fn rebuild_platform_grid(&mut self) {
+        self.platform_grid.clear();
+        for (i, platform) in self.platforms.iter().enumerate() {
+            self.platform_grid
+                .insert(i, platform_swept(platform, self.sprite_scale));
+        }
+    }
+
+    fn rebuild_entity_grid(&mut self) {
+        self.entity_grid.clear();
+        for (i, entity) in self.entities.iter().enumerate() {
+            self.entity_grid.insert(i, entity_rect(entity, self.sprite_scale));
+        }
+    }
+
+    // Indices into `entities` of everything whose box may overlap `area`,
+    // as of the end of the last step. Callers do the exact overlap test.
+    pub fn entities_in_rect(&self, area: Rect) -> Vec<usize> {
+        self.entity_grid.query(area)
+    }
+
     pub fn draw_background(&self) {
         if let Some(bg) = &self.background {
             let screen_h = screen_height();
@@ -603,98 +2108,346 @@ impl Scene {
                 WHITE,
                 DrawTextureParams {
                     dest_size: Some(dest_size),
+                    source: self.background_source,
                     ..Default::default()
                 },
             );
         }
     }
 
-    pub fn draw_world(&self) {
+    // Draws the background layers, each under its own parallax camera, or
+    // the single background at half the world's speed. Leaves the last
+    // layer's camera set.
+    pub fn draw_parallax(&self, camera: &Camera) {
+        if self.background_layers.is_empty() {
+            set_camera(&camera.parallax_camera2d(0.5));
+            self.draw_background();
+            return;
+        }
+        let view_height = camera.view_size().y;
+        for layer in &self.background_layers {
+            set_camera(&camera.parallax_camera2d(layer.def.scroll));
+            let visible = camera.parallax_visible_rect(layer.def.scroll);
+            layer.draw(visible, view_height, self.time);
+        }
+    }
+
+    // Draws everything in world space. `view` is the visible world rect;
+    // tiles outside it are skipped.
+    pub fn draw_world(&self, view: Rect) {
+        if let Some(map) = &self.tilemap {
+            map.draw(view);
+        }
+
         // platforms
         for platform in &self.platforms {
-            let tex = &platform.texture;
-            let dest_size = vec2(tex.width() * self.sprite_scale, tex.height() * self.sprite_scale);
+            let dest_size = platform.size * self.sprite_scale;
+            let pos = platform.prev_position.lerp(platform.position, self.render_alpha);
 
             draw_texture_ex(
-                tex,
-                platform.position.x - dest_size.x / 2.0,
-                platform.position.y - dest_size.y / 2.0,
+                &platform.texture,
+                pos.x - dest_size.x / 2.0,
+                pos.y - dest_size.y / 2.0,
                 WHITE,
                 DrawTextureParams {
                     dest_size: Some(dest_size),
+                    source: platform.source,
                     ..Default::default()
                 },
             );
         }
 
-        // entities (player, enemies, collectibles, projectiles)
+        // ladders, vines and low-gravity fields; water goes over the entities
+        for zone in self.zones.iter().filter(|z| z.rect.overlaps(&view)) {
+            if zone.kind != ZoneKind::Water {
+                zone.draw(self.time);
+            }
+        }
+
+        // entities (player, enemies, collectibles, hazards)
+        let power_up_tint = self.power_ups.tint(self.time);
         for entity in &self.entities {
-            match entity.kind {
-                EntityKind::Projectile => {
-                    let size = 8.0 * self.sprite_scale;
-                    draw_rectangle(
-                        entity.position.x - size / 2.0,
-                        entity.position.y - size / 2.0,
-                        size,
-                        size,
-                        YELLOW,
-                    );
-                }
-                _ => {
-                    let tex = &entity.texture;
-                    let dest_size = vec2(
-                        tex.width() * self.sprite_scale,
-                        tex.height() * self.sprite_scale,
-                    );
-                    let tint = if matches!(entity.kind, EntityKind::Player)
-                        && self.hit_flash_enabled
-                        && self.hit_timer > 0.0
-                    {
-                        RED
-                    } else {
-                        WHITE
-                    };
-                    draw_texture_ex(
-                        tex,
-                        entity.position.x - dest_size.x / 2.0,
-                        entity.position.y - dest_size.y / 2.0,
-                        tint,
-                        DrawTextureParams {
-                            dest_size: Some(dest_size),
-                            ..Default::default()
-                        },
-                    );
+            let is_hazard = matches!(entity.kind, EntityKind::Hazard);
+            if is_hazard && entity.hazard.kind == HazardKind::KillZone {
+                continue;
+            }
+            let pos = entity.prev_position.lerp(entity.position, self.render_alpha);
+            let mut dest_size = entity.size * self.sprite_scale;
+            let hurt = match entity.kind {
+                EntityKind::Player => self.hit_timer > 0.0,
+                EntityKind::Enemy => entity.hurt_timer > 0.0 && entity.dying.is_none(),
+                EntityKind::Collectible
+                | EntityKind::Hazard
+                | EntityKind::Checkpoint
+                | EntityKind::Goal => false,
+            };
+            let mut tint = if self.hit_flash_enabled && hurt {
+                RED
+            } else if matches!(entity.kind, EntityKind::Checkpoint)
+                && self.active_checkpoint != Some(entity.id)
+            {
+                GRAY
+            } else if let (EntityKind::Player, Some(color)) = (&entity.kind, power_up_tint) {
+                color
+            } else {
+                WHITE
+            };
+            // Dying enemies fade out, and are squashed flat (feet kept in
+            // place) unless they have a death animation.
+            let mut foot_shift = 0.0;
+            if let Some(remaining) = entity.dying {
+                let animated = entity
+                    .animator
+                    .as_ref()
+                    .is_some_and(|a| a.has_clip(AnimState::Dead));
+                if !animated {
+                    let full_h = dest_size.y;
+                    dest_size.y *= 0.4;
+                    foot_shift = (full_h - dest_size.y) / 2.0;
                 }
+                tint.a = (remaining / self.enemy_death_duration.max(0.01)).clamp(0.0, 1.0);
             }
+            // Animation frames are whole textures; the atlas region only
+            // applies to the static sprite.
+            let (texture, source) = match entity.animator.as_ref().and_then(|a| a.frame()) {
+                Some(frame) => (frame, None),
+                None => (&entity.texture, entity.source),
+            };
+            let faces_left = entity.animator.as_ref().is_some_and(|a| a.set.faces_left);
+            let rotation = if is_hazard && entity.hazard.kind == HazardKind::Saw {
+                self.time * SAW_SPIN_SPEED
+            } else {
+                0.0
+            };
+            draw_texture_ex(
+                texture,
+                pos.x - dest_size.x / 2.0,
+                pos.y - dest_size.y / 2.0 + foot_shift,
+                tint,
+                DrawTextureParams {
+                    dest_size: Some(dest_size),
+                    source,
+                    flip_x: (entity.facing < 0.0) != faces_left,
+                    rotation,
+                    ..Default::default()
+                },
+            );
+        }
+
+        for zone in self.zones.iter().filter(|z| z.rect.overlaps(&view)) {
+            if zone.kind == ZoneKind::Water {
+                zone.draw(self.time);
+            }
+        }
+
+        // projectiles
+        for p in &self.projectiles {
+            p.draw(p.prev_position.lerp(p.position, self.render_alpha), self.sprite_scale);
         }
 
         // particles
         if self.particles_enabled {
             for p in &self.particles {
-                let alpha = (p.lifetime / 0.5).clamp(0.0, 1.0);
-                let color = Color {
-                    r: 1.0,
-                    g: 1.0,
-                    b: 1.0,
-                    a: alpha,
-                };
-                let size = 4.0 * self.sprite_scale;
-                draw_rectangle(
-                    p.position.x - size / 2.0,
-                    p.position.y - size / 2.0,
-                    size,
-                    size,
-                    color,
-                );
+                p.draw(self.sprite_scale);
             }
         }
     }
 
+    // Lights for the light map, at their drawn positions.
+    pub fn lights(&self) -> Vec<Light> {
+        let config = &self.lighting;
+        let mut lights = Vec::new();
+        for (i, entity) in self.entities.iter().enumerate() {
+            let spec = match entity.kind {
+                EntityKind::Player => config.player,
+                EntityKind::Collectible => config.collectible,
+                _ => continue,
+            };
+            if spec.radius > 0.0 {
+                let pos = entity.prev_position.lerp(entity.position, self.render_alpha);
+                lights.push(config.light(spec, pos, self.time, i as f32 * 1.7));
+            }
+        }
+        if config.projectile.radius > 0.0 {
+            for (i, p) in self.projectiles.iter().enumerate() {
+                let pos = p.prev_position.lerp(p.position, self.render_alpha);
+                lights.push(config.light(config.projectile, pos, self.time, i as f32 * 2.3));
+            }
+        }
+        lights
+    }
+
     pub fn draw(&self) {
         self.draw_background();
-        self.draw_world();
+        self.draw_world(Rect::new(0.0, 0.0, self.world_width, self.world_height));
+    }
+
+}
+
+impl Scene {
+    // The play state `restore` needs to pick the level up from here. Take
+    // it between steps.
+    pub fn snapshot(&self) -> SceneSnapshot {
+        let index = |id: EntityId| self.registry.position(id);
+        SceneSnapshot {
+            entities: self.entities.iter().map(EntitySnapshot::new).collect(),
+            platforms: self.platforms.iter().map(PlatformSnapshot::new).collect(),
+            projectiles: self
+                .projectiles
+                .iter()
+                .map(|p| ProjectileSnapshot {
+                    team: p.team,
+                    owner: p.owner.and_then(index),
+                    size: p.size.to_array(),
+                    position: p.position.to_array(),
+                    prev_position: p.prev_position.to_array(),
+                    velocity: p.velocity.to_array(),
+                    gravity_scale: p.gravity_scale,
+                    lifetime: p.lifetime,
+                    damage: p.damage,
+                    pierce: p.pierce,
+                    collides: p.collides,
+                })
+                .collect(),
+            particles: self
+                .particles
+                .iter()
+                .map(|p| ParticleSnapshot {
+                    effect: p.effect.name.clone(),
+                    position: p.position.to_array(),
+                    velocity: p.velocity.to_array(),
+                    lifetime: p.lifetime,
+                    max_lifetime: p.max_lifetime,
+                })
+                .collect(),
+            emitters: self
+                .emitters
+                .iter()
+                .map(|e| EmitterSnapshot {
+                    effect: e.effect.name.clone(),
+                    position: e.position.to_array(),
+                    remaining: e.remaining,
+                })
+                .collect(),
+            score: self.score,
+            collected: self.collected,
+            player_health: self.player_health,
+            player_max_health: self.player_max_health,
+            enemies_defeated: self.enemies_defeated,
+            hit_timer: self.hit_timer,
+            player_dead: self.player_dead,
+            goal_reached: self.goal_reached,
+            time: self.time,
+            respawn_point: self.respawn_point.to_array(),
+            active_checkpoint: self.active_checkpoint.and_then(index),
+            respawns_left: self.respawns_left,
+            power_ups: self.power_ups.clone(),
+            enemy_jump_timer: self.enemy_jump_timer,
+            enemy_shoot_timer: self.enemy_shoot_timer,
+            player_fire_timer: self.player_fire_timer,
+            accumulator: self.accumulator,
+            player_motion: self.player_motion,
+        }
     }
 
+    // Replaces the entities, platforms and play state with `snapshot`'s,
+    // rebinding sprites from `assets`. Call it on a scene generated from
+    // the rules, level and seed the snapshot was taken with. Leaves the
+    // scene untouched if a sprite is missing.
+    pub fn restore(&mut self, snapshot: &SceneSnapshot, assets: &Assets) -> Result<(), String> {
+        let entities = snapshot
+            .entities
+            .iter()
+            .map(|e| e.to_entity(assets))
+            .collect::<Result<Vec<_>, _>>()?;
+        let platforms = snapshot
+            .platforms
+            .iter()
+            .map(|p| p.to_platform(assets))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.registry = EntityRegistry::default();
+        self.entities.clear();
+        self.pending_spawns.clear();
+        self.pending_despawns.clear();
+        let ids: Vec<EntityId> = entities.into_iter().map(|e| self.add_entity(e)).collect();
+        let id_at = |index: usize| ids.get(index).copied();
+        self.platforms = platforms;
+
+        self.projectiles = snapshot
+            .projectiles
+            .iter()
+            .map(|p| {
+                let shot = match p.team {
+                    Team::Player => &self.player_shot,
+                    Team::Enemy => &self.enemy_shot,
+                };
+                Projectile {
+                    team: p.team,
+                    owner: p.owner.and_then(id_at),
+                    texture: shot.texture.clone(),
+                    source: shot.source,
+                    size: Vec2::from(p.size),
+                    position: Vec2::from(p.position),
+                    prev_position: Vec2::from(p.prev_position),
+                    velocity: Vec2::from(p.velocity),
+                    gravity_scale: p.gravity_scale,
+                    lifetime: p.lifetime,
+                    damage: p.damage,
+                    pierce: p.pierce,
+                    collides: p.collides,
+                }
+            })
+            .collect();
+        self.particles = snapshot
+            .particles
+            .iter()
+            .filter_map(|p| {
+                Some(Particle {
+                    position: Vec2::from(p.position),
+                    velocity: Vec2::from(p.velocity),
+                    lifetime: p.lifetime,
+                    max_lifetime: p.max_lifetime,
+                    effect: self.effects.get(&p.effect)?.clone(),
+                })
+            })
+            .collect();
+        self.emitters = snapshot
+            .emitters
+            .iter()
+            .filter_map(|e| {
+                let effect = self.effects.get(&e.effect)?.clone();
+                let mut emitter = Emitter::new(effect, Vec2::from(e.position));
+                emitter.remaining = e.remaining;
+                Some(emitter)
+            })
+            .collect();
+
+        self.score = snapshot.score;
+        self.collected = snapshot.collected;
+        self.player_health = snapshot.player_health;
+        self.player_max_health = snapshot.player_max_health;
+        self.enemies_defeated = snapshot.enemies_defeated;
+        self.hit_timer = snapshot.hit_timer;
+        self.player_dead = snapshot.player_dead;
+        self.goal_reached = snapshot.goal_reached;
+        self.time = snapshot.time;
+        self.respawn_point = Vec2::from(snapshot.respawn_point);
+        self.active_checkpoint = snapshot.active_checkpoint.and_then(id_at);
+        self.respawns_left = snapshot.respawns_left;
+        self.power_ups = snapshot.power_ups.clone();
+        self.enemy_jump_timer = snapshot.enemy_jump_timer;
+        self.enemy_shoot_timer = snapshot.enemy_shoot_timer;
+        self.player_fire_timer = snapshot.player_fire_timer;
+        self.accumulator = snapshot.accumulator;
+        self.render_alpha = (self.accumulator / self.fixed_dt).clamp(0.0, 1.0);
+        self.player_motion = snapshot.player_motion;
+        self.pending_input = InputFrame::default();
+        self.events.clear();
+        self.rebuild_platform_grid();
+        self.rebuild_entity_grid();
+        Ok(())
+    }
 }
 
 impl Scene {
@@ -705,13 +2458,35 @@ impl Scene {
             .map(|e| e.position)
     }
 
+    pub fn player_velocity(&self) -> Option<Vec2> {
+        self.entities
+            .iter()
+            .find(|e| matches!(e.kind, EntityKind::Player))
+            .map(|e| e.velocity + e.momentum)
+    }
+
+    // Player position as drawn this frame (interpolated between steps).
+    pub fn player_render_position(&self) -> Option<Vec2> {
+        self.entities
+            .iter()
+            .find(|e| matches!(e.kind, EntityKind::Player))
+            .map(|e| e.prev_position.lerp(e.position, self.render_alpha))
+    }
+
     pub fn debug_draw(&self) {
         let scale = self.sprite_scale;
 
         // Platforms
         for platform in &self.platforms {
             let r = platform_rect(platform, scale);
-            draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, GREEN);
+            let color = if platform.one_way { GREEN } else { DARKGREEN };
+            draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, color);
+        }
+
+        // Zones
+        for zone in &self.zones {
+            let r = zone.rect;
+            draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, SKYBLUE);
         }
 
         // Entities
@@ -721,115 +2496,286 @@ impl Scene {
                 EntityKind::Player => BLUE,
                 EntityKind::Enemy => RED,
                 EntityKind::Collectible => YELLOW,
-                EntityKind::Projectile => ORANGE,
+                EntityKind::Hazard => MAGENTA,
+                EntityKind::Checkpoint => WHITE,
+                EntityKind::Goal => GREEN,
             };
             draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, color);
         }
+
+        // Projectiles
+        for p in &self.projectiles {
+            let r = p.rect(scale);
+            draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, ORANGE);
+        }
     }
 }
 
 fn entity_rect(entity: &Entity, scale: f32) -> Rect {
-    match entity.kind {
-        EntityKind::Projectile => {
-            let size = 8.0 * scale;
-            Rect::new(
-                entity.position.x - size / 2.0,
-                entity.position.y - size / 2.0,
-                size,
-                size,
-            )
-        }
-        _ => {
-            let w = entity.texture.width() * scale;
-            let h = entity.texture.height() * scale;
-            Rect::new(
-                entity.position.x - w / 2.0,
-                entity.position.y - h / 2.0,
-                w,
-                h,
-            )
-        }
-    }
-}
-
-fn emit_particles(
-    out: &mut Vec<Particle>,
-    origin: Vec2,
-    count: u32,
-    angle_start: f32,
-    angle_end: f32,
-    speed: f32,
-    lifetime: f32,
-) {
-    if count == 0 {
-        return;
-    }
-    let total = count.max(1);
-    let delta = if total <= 1 {
-        0.0
-    } else {
-        (angle_end - angle_start) / (total as f32)
-    };
-    for i in 0..total {
-        let angle = angle_start + delta * i as f32;
-        let dir = vec2(angle.cos(), angle.sin());
-        out.push(Particle {
-            position: origin,
-            velocity: dir * speed,
-            lifetime,
-        });
-    }
+    let w = entity.size.x * scale;
+    let h = entity.size.y * scale;
+    Rect::new(
+        entity.position.x - w / 2.0,
+        entity.position.y - h / 2.0,
+        w,
+        h,
+    )
 }
 
 fn platform_rect(platform: &Platform, scale: f32) -> Rect {
-    let w = platform.texture.width() * scale;
-    let h = platform.texture.height() * scale;
+    let w = platform.size.x * scale;
+    let h = platform.size.y * scale;
     Rect::new(platform.position.x - w / 2.0, platform.position.y - h / 2.0, w, h)
 }
 
-fn is_on_ground(entity: &Entity, platforms: &[Platform], scale: f32) -> bool {
-    let mut rect = entity_rect(entity, scale);
-    rect.y += 1.0;
-    for platform in platforms {
-        let plat = platform_rect(platform, scale);
-        if rect.overlaps(&plat) {
-            return true;
+// Slack used when comparing edges, so entities resting exactly on a surface
+// are neither treated as overlapping it nor as floating above it.
+const CONTACT_EPSILON: f32 = 0.01;
+
+// How far the player's feet may already be inside an enemy for landing on it
+// to still count as a stomp.
+const STOMP_TOLERANCE: f32 = 4.0;
+
+// How long an enemy ignores further hits after being damaged.
+const ENEMY_HURT_DURATION: f32 = 0.25;
+
+// Radians per second saw blades turn while drawn.
+const SAW_SPIN_SPEED: f32 = 8.0;
+
+// How far a platform moved up this step.
+fn platform_rise(platform: &Platform) -> f32 {
+    (platform.prev_position.y - platform.position.y).max(0.0)
+}
+
+// Area a platform covered over the last step, from its previous box to its
+// current one. Covers everything it may have pushed into or carried.
+fn platform_swept(platform: &Platform, scale: f32) -> Rect {
+    let rect = platform_rect(platform, scale);
+    let delta = platform.prev_position - platform.position;
+    Rect::new(
+        rect.x + delta.x.min(0.0),
+        rect.y + delta.y.min(0.0),
+        rect.w + delta.x.abs(),
+        rect.h + delta.y.abs(),
+    )
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CollisionHits {
+    left: bool,
+    right: bool,
+    ceiling: bool,
+    ground: bool,
+}
+
+// A blocking box gathered from platforms or tiles for one collision query.
+#[derive(Debug, Clone, Copy)]
+struct Collider {
+    rect: Rect,
+    one_way: bool,
+    // How far the collider moved up this step; entities it rose into still
+    // count as being on top of it.
+    rise: f32,
+}
+
+// Everything entities can stand on or bump into, borrowed for a step.
+struct Solids<'a> {
+    platforms: &'a [Platform],
+    platform_grid: &'a SpatialGrid,
+    tilemap: Option<&'a TileMap>,
+    scale: f32,
+}
+
+impl Solids<'_> {
+    fn colliders_in(&self, area: Rect) -> Vec<Collider> {
+        let mut out = Vec::new();
+        for i in self.platform_grid.query(area) {
+            let platform = &self.platforms[i];
+            if platform_swept(platform, self.scale).overlaps(&area) {
+                out.push(Collider {
+                    rect: platform_rect(platform, self.scale),
+                    one_way: platform.one_way,
+                    rise: platform_rise(platform),
+                });
+            }
+        }
+        if let Some(map) = self.tilemap {
+            for (rect, tile) in map.tiles_in_rect(area) {
+                let one_way = match tile.kind {
+                    TileKind::Solid => false,
+                    TileKind::OneWay => true,
+                    _ => continue,
+                };
+                out.push(Collider {
+                    rect,
+                    one_way,
+                    rise: 0.0,
+                });
+            }
         }
+        out
     }
-    false
-}
 
-fn resolve_platform_collisions(entity: &mut Entity, platforms: &[Platform], scale: f32) {
-    if entity.velocity.y <= 0.0 {
-        return;
+    // If `entity` was standing on a platform at the start of the step, moves
+    // it along with the platform and returns the platform's velocity. The
+    // entity's own velocity is left untouched.
+    fn carry_rider(&self, entity: &mut Entity) -> Option<Vec2> {
+        if entity.velocity.y < 0.0 {
+            return None;
+        }
+        let rect = entity_rect(entity, self.scale);
+        let bottom = rect.bottom();
+        let probe = Rect::new(rect.x, bottom - 1.0, rect.w, 2.0);
+        let platform = self.platform_grid.query(probe).into_iter().find_map(|i| {
+            let platform = &self.platforms[i];
+            let size = platform.size * self.scale;
+            let prev = Rect::new(
+                platform.prev_position.x - size.x / 2.0,
+                platform.prev_position.y - size.y / 2.0,
+                size.x,
+                size.y,
+            );
+            (overlaps_x(&rect, &prev)
+                && bottom >= prev.y - 1.0
+                && bottom <= prev.y + CONTACT_EPSILON)
+                .then_some(platform)
+        })?;
+
+        let delta = platform.position - platform.prev_position;
+        if delta != Vec2::ZERO {
+            let velocity = entity.velocity;
+            self.move_and_collide(entity, delta);
+            entity.velocity = velocity;
+        }
+        Some(platform.velocity)
     }
 
-    let mut rect = entity_rect(entity, scale);
+    // Whether there is something to stand on just past the leading edge of
+    // `entity` when walking in direction `dir`.
+    fn has_ground_ahead(&self, entity: &Entity, dir: f32) -> bool {
+        let rect = entity_rect(entity, self.scale);
+        let x = if dir > 0.0 { rect.right() } else { rect.x - 2.0 };
+        let probe = Rect::new(x, rect.bottom(), 2.0, 4.0);
+        self.colliders_in(probe)
+            .iter()
+            .any(|c| c.rect.overlaps(&probe))
+    }
 
-    for platform in platforms {
-        let plat = platform_rect(platform, scale);
-        if !rect.overlaps(&plat) {
-            continue;
-        }
+    fn is_on_ground(&self, entity: &Entity) -> bool {
+        let rect = entity_rect(entity, self.scale);
+        let bottom = rect.bottom();
+        let probe = Rect::new(rect.x, bottom - 1.0, rect.w, 2.0);
+        self.colliders_in(probe).iter().any(|c| {
+            overlaps_x(&rect, &c.rect)
+                && bottom >= c.rect.y - 1.0
+                && bottom <= c.rect.y + c.rise + CONTACT_EPSILON
+        })
+    }
 
-        // landed on top of platform
-        entity.position.y = plat.y - rect.h / 2.0;
-        entity.velocity.y = 0.0;
-        rect.y = plat.y - rect.h;
+    // Whether a solid collider is directly beside `entity` on the `dir` side
+    // (-1.0 left, 1.0 right).
+    fn touching_wall(&self, entity: &Entity, dir: f32) -> bool {
+        let rect = entity_rect(entity, self.scale);
+        let x = if dir < 0.0 { rect.x - 1.0 } else { rect.right() };
+        let probe = Rect::new(x, rect.y + 2.0, 1.0, (rect.h - 4.0).max(1.0));
+        self.colliders_in(probe)
+            .iter()
+            .any(|c| !c.one_way && c.rect.overlaps(&probe))
     }
-}
 
-fn play_sound_opt(sound: &Option<Sound>, volume: f32, enabled: bool) {
-    if !enabled {
-        return;
+    // Moves `entity` by `delta`, sweeping its box one axis at a time:
+    // horizontal first (solid colliders only), then vertical. Velocity on a
+    // blocked axis is zeroed.
+    fn move_and_collide(&self, entity: &mut Entity, delta: Vec2) -> CollisionHits {
+        self.move_through(entity, delta, f32::NEG_INFINITY)
     }
-    if let Some(s) = sound.as_ref() {
-        audio::play_sound(
-            s,
-            PlaySoundParams {
-                looped: false,
-                volume,
-            },
+
+    // `move_and_collide`, except falling passes one-way colliders whose top
+    // is above `floor`.
+    fn move_through(&self, entity: &mut Entity, delta: Vec2, floor: f32) -> CollisionHits {
+        let mut hits = CollisionHits::default();
+        let rect = entity_rect(entity, self.scale);
+        let swept = Rect::new(
+            rect.x + delta.x.min(0.0),
+            rect.y + delta.y.min(0.0),
+            rect.w + delta.x.abs(),
+            rect.h + delta.y.abs(),
         );
+        let colliders = self.colliders_in(swept);
+
+        let mut dx = delta.x;
+        if dx != 0.0 {
+            for c in colliders.iter().filter(|c| !c.one_way) {
+                if !overlaps_y(&rect, &c.rect) {
+                    continue;
+                }
+                if dx > 0.0
+                    && rect.right() <= c.rect.x + CONTACT_EPSILON
+                    && rect.right() + dx > c.rect.x
+                {
+                    dx = c.rect.x - rect.right();
+                    hits.right = true;
+                } else if dx < 0.0
+                    && rect.x >= c.rect.right() - CONTACT_EPSILON
+                    && rect.x + dx < c.rect.right()
+                {
+                    dx = c.rect.right() - rect.x;
+                    hits.left = true;
+                }
+            }
+            if hits.left || hits.right {
+                entity.velocity.x = 0.0;
+            }
+        }
+        entity.position.x += dx;
+
+        let rect = entity_rect(entity, self.scale);
+        let mut dy = delta.y;
+        if dy > 0.0 {
+            let bottom = rect.bottom();
+            for c in &colliders {
+                if !overlaps_x(&rect, &c.rect) || (c.one_way && c.rect.y < floor) {
+                    continue;
+                }
+                let was_above = bottom <= c.rect.y + c.rise + CONTACT_EPSILON;
+                if was_above && bottom + dy >= c.rect.y {
+                    dy = c.rect.y - bottom;
+                    hits.ground = true;
+                }
+            }
+        } else if dy < 0.0 {
+            for c in colliders.iter().filter(|c| !c.one_way) {
+                if !overlaps_x(&rect, &c.rect) {
+                    continue;
+                }
+                if rect.y >= c.rect.bottom() - CONTACT_EPSILON && rect.y + dy < c.rect.bottom() {
+                    dy = c.rect.bottom() - rect.y;
+                    hits.ceiling = true;
+                }
+            }
+        }
+        if hits.ground || hits.ceiling {
+            entity.velocity.y = 0.0;
+        }
+        entity.position.y += dy;
+
+        hits
     }
 }
+
+// Moves `current` towards `target` by at most `max_delta`.
+fn approach(current: f32, target: f32, max_delta: f32) -> f32 {
+    if current < target {
+        (current + max_delta).min(target)
+    } else {
+        (current - max_delta).max(target)
+    }
+}
+
+fn overlaps_x(a: &Rect, b: &Rect) -> bool {
+    a.x < b.right() - CONTACT_EPSILON && a.right() > b.x + CONTACT_EPSILON
+}
+
+fn overlaps_y(a: &Rect, b: &Rect) -> bool {
+    a.y < b.bottom() - CONTACT_EPSILON && a.bottom() > b.y + CONTACT_EPSILON
+}
@@ -0,0 +1,254 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{AnimState, Animator};
+use crate::assets::{headless_texture, Assets, SpriteAsset, SpriteKind};
+use crate::powerup::{PowerUp, PowerUps};
+use crate::projectile::Team;
+use crate::scene::{EnemyBehavior, Entity, EntityKind, Hazard, Platform, PlayerMotion};
+
+// A scene mid-level, without textures, so it can be written to disk.
+// Sprites are referenced by name and rebound from `Assets` by
+// `Scene::restore`. Only what changes during play is recorded: restore onto
+// a scene generated from the same rules, level and seed, which supplies the
+// tiles, zones, background and settings. Vectors are [x, y]. The particle
+// RNG and emitters' fractional carry aren't recorded, so particles spawned
+// after a restore differ; they don't affect play.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneSnapshot {
+    pub entities: Vec<EntitySnapshot>,
+    pub platforms: Vec<PlatformSnapshot>,
+    pub projectiles: Vec<ProjectileSnapshot>,
+    pub particles: Vec<ParticleSnapshot>,
+    pub emitters: Vec<EmitterSnapshot>,
+    pub score: u32,
+    pub collected: u32,
+    pub player_health: u32,
+    pub player_max_health: u32,
+    pub enemies_defeated: u32,
+    pub hit_timer: f32,
+    pub player_dead: bool,
+    pub goal_reached: bool,
+    pub time: f32,
+    pub respawn_point: [f32; 2],
+    // Index into `entities`.
+    pub active_checkpoint: Option<usize>,
+    pub respawns_left: u32,
+    pub power_ups: PowerUps,
+    pub enemy_jump_timer: f32,
+    pub enemy_shoot_timer: f32,
+    pub player_fire_timer: f32,
+    pub accumulator: f32,
+    pub player_motion: PlayerMotion,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    pub kind: EntityKind,
+    // Sprite name; empty for entities drawn without one, like kill zones.
+    pub name: String,
+    pub tag: Option<String>,
+    pub size: [f32; 2],
+    pub position: [f32; 2],
+    pub prev_position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub value: u32,
+    pub health_value: u32,
+    pub base_position: [f32; 2],
+    pub phase: f32,
+    pub jumping: bool,
+    pub momentum: [f32; 2],
+    pub behavior: EnemyBehavior,
+    pub cooldown: f32,
+    pub awake: bool,
+    pub health: u32,
+    pub dying: Option<f32>,
+    pub hurt_timer: f32,
+    pub facing: f32,
+    // Current clip and time into it, for animated sprites.
+    pub animation: Option<(AnimState, f32)>,
+    pub hazard: Hazard,
+    pub power_up: Option<PowerUp>,
+}
+
+impl EntitySnapshot {
+    pub fn new(entity: &Entity) -> Self {
+        Self {
+            kind: entity.kind,
+            name: entity.name.clone(),
+            tag: entity.tag.clone(),
+            size: entity.size.to_array(),
+            position: entity.position.to_array(),
+            prev_position: entity.prev_position.to_array(),
+            velocity: entity.velocity.to_array(),
+            value: entity.value,
+            health_value: entity.health_value,
+            base_position: entity.base_position.to_array(),
+            phase: entity.phase,
+            jumping: entity.jumping,
+            momentum: entity.momentum.to_array(),
+            behavior: entity.behavior,
+            cooldown: entity.cooldown,
+            awake: entity.awake,
+            health: entity.health,
+            dying: entity.dying,
+            hurt_timer: entity.hurt_timer,
+            facing: entity.facing,
+            animation: entity.animator.as_ref().map(|a| (a.state, a.time)),
+            hazard: entity.hazard,
+            power_up: entity.power_up,
+        }
+    }
+
+    // The entity with its sprite and animations looked up by name. It
+    // still needs adding to a scene for an id.
+    pub fn to_entity(&self, assets: &Assets) -> Result<Entity, String> {
+        let (texture, source) = if self.name.is_empty() {
+            (headless_texture(), None)
+        } else {
+            let sprite = entity_sprite(assets, self.kind, &self.name)
+                .ok_or_else(|| format!("Snapshot: no sprite found for '{}'", self.name))?;
+            (sprite.texture.clone(), sprite.source)
+        };
+        let animator = match (self.animation, assets.animation(&self.name)) {
+            (Some((state, time)), Some(set)) => Some(Animator {
+                state,
+                time,
+                ..Animator::new(set)
+            }),
+            _ => None,
+        };
+        let position = Vec2::from(self.position);
+        Ok(Entity {
+            name: self.name.clone(),
+            tag: self.tag.clone(),
+            source,
+            prev_position: Vec2::from(self.prev_position),
+            velocity: Vec2::from(self.velocity),
+            value: self.value,
+            health_value: self.health_value,
+            base_position: Vec2::from(self.base_position),
+            phase: self.phase,
+            jumping: self.jumping,
+            momentum: Vec2::from(self.momentum),
+            behavior: self.behavior,
+            cooldown: self.cooldown,
+            awake: self.awake,
+            health: self.health,
+            dying: self.dying,
+            hurt_timer: self.hurt_timer,
+            facing: self.facing,
+            animator,
+            hazard: self.hazard,
+            power_up: self.power_up,
+            ..Entity::new(self.kind, texture, Vec2::from(self.size), position)
+        })
+    }
+}
+
+// Collectibles may be plain, goal or power-up sprites.
+fn entity_sprite<'a>(assets: &'a Assets, kind: EntityKind, name: &str) -> Option<&'a SpriteAsset> {
+    let kinds: &[SpriteKind] = match kind {
+        EntityKind::Player => &[SpriteKind::Player],
+        EntityKind::Enemy => &[SpriteKind::Enemy],
+        EntityKind::Collectible => &[
+            SpriteKind::Collectible,
+            SpriteKind::GoalCollectible,
+            SpriteKind::PowerUp,
+        ],
+        EntityKind::Hazard => &[SpriteKind::Hazard],
+        EntityKind::Checkpoint => &[SpriteKind::Checkpoint],
+        EntityKind::Goal => &[SpriteKind::GoalCollectible],
+    };
+    kinds
+        .iter()
+        .find_map(|kind| assets.sprite_by_kind_and_name(*kind, name))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformSnapshot {
+    pub name: String,
+    pub size: [f32; 2],
+    pub position: [f32; 2],
+    pub prev_position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub base_position: [f32; 2],
+    pub phase: f32,
+    pub moving: bool,
+    pub vertical: bool,
+    pub one_way: bool,
+}
+
+impl PlatformSnapshot {
+    pub fn new(platform: &Platform) -> Self {
+        Self {
+            name: platform.name.clone(),
+            size: platform.size.to_array(),
+            position: platform.position.to_array(),
+            prev_position: platform.prev_position.to_array(),
+            velocity: platform.velocity.to_array(),
+            base_position: platform.base_position.to_array(),
+            phase: platform.phase,
+            moving: platform.moving,
+            vertical: platform.vertical,
+            one_way: platform.one_way,
+        }
+    }
+
+    pub fn to_platform(&self, assets: &Assets) -> Result<Platform, String> {
+        let sprite = assets
+            .sprite_by_kind_and_name(SpriteKind::Platform, &self.name)
+            .ok_or_else(|| format!("Snapshot: no platform sprite found for '{}'", self.name))?;
+        Ok(Platform {
+            name: self.name.clone(),
+            source: sprite.source,
+            prev_position: Vec2::from(self.prev_position),
+            velocity: Vec2::from(self.velocity),
+            base_position: Vec2::from(self.base_position),
+            phase: self.phase,
+            moving: self.moving,
+            vertical: self.vertical,
+            one_way: self.one_way,
+            ..Platform::new(
+                sprite.texture.clone(),
+                Vec2::from(self.size),
+                Vec2::from(self.position),
+            )
+        })
+    }
+}
+
+// Projectiles take their sprite from the scene's shot of their team.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectileSnapshot {
+    pub team: Team,
+    // Index into the snapshot's entities.
+    pub owner: Option<usize>,
+    pub size: [f32; 2],
+    pub position: [f32; 2],
+    pub prev_position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub gravity_scale: f32,
+    pub lifetime: f32,
+    pub damage: u32,
+    pub pierce: u32,
+    pub collides: bool,
+}
+
+// Particles and emitters refer to the scene's effects by name; ones whose
+// effect is gone are dropped on restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleSnapshot {
+    pub effect: String,
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub lifetime: f32,
+    pub max_lifetime: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmitterSnapshot {
+    pub effect: String,
+    pub position: [f32; 2],
+    pub remaining: f32,
+}
@@ -0,0 +1,68 @@
+use macroquad::prelude::Rect;
+use std::collections::HashMap;
+
+// Uniform-grid broadphase. Items are stored by index under every cell their
+// box touches; queries return the indices in the cells an area touches, so
+// callers still do their own exact overlap test.
+//
+// Cells are hashed rather than stored in a fixed array, so the grid works
+// for any world size and items outside the world bounds.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(1.0),
+            cells: HashMap::new(),
+        }
+    }
+
+    // Empties every cell but keeps their allocations for the next rebuild.
+    pub fn clear(&mut self) {
+        for items in self.cells.values_mut() {
+            items.clear();
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, rect: Rect) {
+        let (min, max) = self.cell_span(rect);
+        for cy in min.1..=max.1 {
+            for cx in min.0..=max.0 {
+                self.cells.entry((cx, cy)).or_default().push(index);
+            }
+        }
+    }
+
+    // Indices of items whose cells overlap `area`, sorted and without
+    // duplicates. Results are appended to `out` after clearing it.
+    pub fn query_into(&self, area: Rect, out: &mut Vec<usize>) {
+        out.clear();
+        let (min, max) = self.cell_span(area);
+        for cy in min.1..=max.1 {
+            for cx in min.0..=max.0 {
+                if let Some(items) = self.cells.get(&(cx, cy)) {
+                    out.extend_from_slice(items);
+                }
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+
+    pub fn query(&self, area: Rect) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.query_into(area, &mut out);
+        out
+    }
+
+    fn cell_span(&self, rect: Rect) -> ((i32, i32), (i32, i32)) {
+        let cell = |v: f32| (v / self.cell_size).floor() as i32;
+        (
+            (cell(rect.x), cell(rect.y)),
+            (cell(rect.x + rect.w), cell(rect.y + rect.h)),
+        )
+    }
+}
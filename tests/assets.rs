@@ -0,0 +1,18 @@
+// The shipped assets, loaded without a window.
+use engine::assets::{load_assets_headless, SpriteKind};
+use macroquad::prelude::*;
+
+#[test]
+fn atlas_sprites_use_their_sub_texture() {
+    let assets = load_assets_headless("assets").unwrap();
+    let sprite = assets
+        .sprite_by_kind_and_name(SpriteKind::Enemy, "blockerMad")
+        .unwrap();
+    // <SubTexture name="blockerMad.png" x="113" y="118" width="51" height="51"/>
+    assert_eq!(sprite.source, Some(Rect::new(113.0, 118.0, 51.0, 51.0)));
+    assert_eq!(sprite.size, vec2(51.0, 51.0));
+    // Only the listed sub-textures are loaded.
+    assert!(assets
+        .sprite_by_kind_and_name(SpriteKind::Enemy, "blockerSad")
+        .is_none());
+}
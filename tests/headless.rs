@@ -0,0 +1,172 @@
+// Seeded levels driven by scripted input, without a window or audio device.
+use engine::assets::{headless_texture, load_assets_headless, Assets};
+use engine::events::GameEvent;
+use engine::generator::{generate_scene_for_seed, GameRules};
+use engine::input::{InputFrame, ScriptedInput};
+use engine::scene::{EnemyBehavior, Entity, EntityKind, Scene};
+use engine::snapshot::SceneSnapshot;
+use macroquad::prelude::*;
+
+const SEED: u64 = 7;
+const STEPS: usize = 600;
+
+fn new_scene(assets: &Assets, rules: &GameRules) -> Scene {
+    let world_size = vec2(
+        1280.0 * rules.world_width_screens.max(1.0),
+        720.0 * rules.world_height_screens.max(1.0),
+    );
+    generate_scene_for_seed(assets, rules, 1, world_size, SEED)
+}
+
+// Runs right, jumping now and then, with fire held.
+fn script() -> ScriptedInput {
+    let run = InputFrame {
+        right: true,
+        fire: true,
+        ..Default::default()
+    };
+    let jump = InputFrame {
+        jump: true,
+        jump_held: true,
+        ..run
+    };
+    let mut input = ScriptedInput::new(vec![]);
+    for _ in 0..STEPS / 60 {
+        input = input.hold(jump, 1).hold(run, 59);
+    }
+    input
+}
+
+// One fixed step per poll, returning the events in order.
+fn run(scene: &mut Scene, input: &mut ScriptedInput, steps: usize) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    for _ in 0..steps {
+        scene.update(scene.fixed_dt, input);
+        events.extend(scene.take_events());
+    }
+    events
+}
+
+#[test]
+fn same_seed_and_input_play_the_same() {
+    let assets = load_assets_headless("assets").unwrap();
+    let rules = GameRules::default();
+
+    let mut first = new_scene(&assets, &rules);
+    let first_events = run(&mut first, &mut script(), STEPS);
+    let mut second = new_scene(&assets, &rules);
+    let second_events = run(&mut second, &mut script(), STEPS);
+
+    assert!(!first_events.is_empty());
+    assert_eq!(first_events, second_events);
+    assert_eq!(first.player_position(), second.player_position());
+    assert_eq!(first.score, second.score);
+    assert_eq!(first.player_health, second.player_health);
+}
+
+fn entity_positions(scene: &Scene) -> Vec<Vec2> {
+    scene.entities.iter().map(|e| e.position).collect()
+}
+
+// Frames at `frame_dt` until `steps` fixed steps have run, or as many as
+// `frames` give when `steps` is `None`. Returns the steps run and events.
+fn run_frames(
+    scene: &mut Scene,
+    frame_dt: f32,
+    frames: usize,
+    steps: Option<u32>,
+) -> (u32, Vec<GameEvent>) {
+    // Jump is held throughout so only the first press matters, which waits
+    // for the first step however short the frame.
+    let run = InputFrame {
+        right: true,
+        jump_held: true,
+        fire: true,
+        ..Default::default()
+    };
+    let mut input = ScriptedInput::new(vec![InputFrame { jump: true, ..run }]).hold(run, frames);
+    let mut ran = 0;
+    let mut events = Vec::new();
+    for _ in 0..frames {
+        if steps.is_some_and(|steps| ran >= steps) {
+            break;
+        }
+        ran += scene.advance(frame_dt, &mut input);
+        events.extend(scene.take_events());
+    }
+    (ran, events)
+}
+
+#[test]
+fn frame_rate_does_not_change_gameplay() {
+    let assets = load_assets_headless("assets").unwrap();
+    let rules = GameRules::default();
+
+    let mut slow = new_scene(&assets, &rules);
+    let (steps, slow_events) = run_frames(&mut slow, 1.0 / 30.0, 150, None);
+    // At 144 fps each frame runs at most one step, so this stops on the
+    // same step count.
+    let mut fast = new_scene(&assets, &rules);
+    let (fast_steps, fast_events) = run_frames(&mut fast, 1.0 / 144.0, 2000, Some(steps));
+
+    assert!(steps > 0);
+    assert_eq!(steps, fast_steps);
+    assert_eq!(slow_events, fast_events);
+    assert_eq!(slow.time, fast.time);
+    assert_eq!(slow.player_position(), fast.player_position());
+    assert_eq!(slow.player_velocity(), fast.player_velocity());
+    assert_eq!(slow.score, fast.score);
+    assert_eq!(slow.player_health, fast.player_health);
+    assert_eq!(entity_positions(&slow), entity_positions(&fast));
+}
+
+// The snapshot as JSON, less the particles, which may differ after a
+// restore.
+fn play_state(scene: &Scene) -> serde_json::Value {
+    let mut snapshot = scene.snapshot();
+    snapshot.particles.clear();
+    snapshot.emitters.clear();
+    serde_json::to_value(snapshot).unwrap()
+}
+
+#[test]
+fn restored_snapshot_plays_the_same() {
+    let assets = load_assets_headless("assets").unwrap();
+    let rules = GameRules::default();
+
+    let mut original = new_scene(&assets, &rules);
+    // An ambusher behind the player, out of its line of fire, awake by the
+    // snapshot.
+    let start = original.player_position().unwrap();
+    let ambusher = original.add_entity(Entity {
+        behavior: EnemyBehavior::Ambusher { range: 300.0 },
+        health: 1,
+        ..Entity::new(
+            EntityKind::Enemy,
+            headless_texture(),
+            vec2(32.0, 32.0),
+            start - vec2(150.0, 0.0),
+        )
+    });
+    let mut input = script();
+    run(&mut original, &mut input, STEPS / 2);
+    let awake = |scene: &Scene| scene.entities.iter().any(|e| e.id == ambusher && e.awake);
+    assert!(awake(&original));
+    let text = serde_json::to_string(&original.snapshot()).unwrap();
+    let snapshot: SceneSnapshot = serde_json::from_str(&text).unwrap();
+    let mut restored = new_scene(&assets, &rules);
+    restored.restore(&snapshot, &assets).unwrap();
+    assert_eq!(play_state(&original), play_state(&restored));
+    let woken = restored.entities.iter().filter(|e| e.awake).count();
+    assert_eq!(woken, 1);
+
+    let original_events = run(&mut original, &mut input.clone(), STEPS / 2);
+    let restored_events = run(&mut restored, &mut input, STEPS / 2);
+
+    assert_eq!(original_events, restored_events);
+    assert_eq!(entity_positions(&original), entity_positions(&restored));
+    assert_eq!(original.score, restored.score);
+    assert_eq!(original.player_health, restored.player_health);
+    assert_eq!(original.time, restored.time);
+    assert_eq!(play_state(&original), play_state(&restored));
+}
@@ -0,0 +1,92 @@
+// Power-up timers and shield charges.
+use engine::powerup::{PowerUp, PowerUpConfig, PowerUpKind, PowerUpStacking, PowerUps};
+
+fn pickup(kind: PowerUpKind, duration: f32) -> PowerUp {
+    PowerUp { kind, duration }
+}
+
+fn remaining(power_ups: &PowerUps, kind: PowerUpKind) -> Option<f32> {
+    power_ups
+        .active()
+        .iter()
+        .find(|a| a.kind == kind)
+        .map(|a| a.remaining)
+}
+
+#[test]
+fn refresh_starts_the_timer_over() {
+    let config = PowerUpConfig {
+        stacking: PowerUpStacking::Refresh,
+        ..Default::default()
+    };
+    let mut power_ups = PowerUps::default();
+    power_ups.apply(pickup(PowerUpKind::Speed, 8.0), &config);
+    power_ups.tick(1.0);
+    power_ups.apply(pickup(PowerUpKind::Speed, 3.0), &config);
+
+    assert_eq!(remaining(&power_ups, PowerUpKind::Speed), Some(3.0));
+    assert_eq!(power_ups.active()[0].duration, 3.0);
+    assert_eq!(power_ups.active().len(), 1);
+}
+
+#[test]
+fn extend_adds_up_to_the_max_duration() {
+    let config = PowerUpConfig {
+        stacking: PowerUpStacking::Extend,
+        duration: 8.0,
+        max_duration: 20.0,
+        ..Default::default()
+    };
+    let mut power_ups = PowerUps::default();
+    // A zero duration uses the config's.
+    power_ups.apply(pickup(PowerUpKind::Magnet, 0.0), &config);
+    power_ups.apply(pickup(PowerUpKind::Magnet, 0.0), &config);
+    assert_eq!(remaining(&power_ups, PowerUpKind::Magnet), Some(16.0));
+
+    power_ups.apply(pickup(PowerUpKind::Magnet, 0.0), &config);
+    assert_eq!(remaining(&power_ups, PowerUpKind::Magnet), Some(20.0));
+}
+
+#[test]
+fn timers_run_out() {
+    let config = PowerUpConfig::default();
+    let mut power_ups = PowerUps::default();
+    power_ups.apply(pickup(PowerUpKind::HighJump, 2.0), &config);
+    power_ups.apply(pickup(PowerUpKind::Speed, 5.0), &config);
+
+    assert!(power_ups.tick(1.5).is_empty());
+    assert_eq!(power_ups.tick(1.0), vec![PowerUpKind::HighJump]);
+    assert!(!power_ups.is_active(PowerUpKind::HighJump));
+    assert!(power_ups.is_active(PowerUpKind::Speed));
+}
+
+#[test]
+fn shield_charges_are_capped() {
+    let config = PowerUpConfig {
+        shield_charges: 2,
+        shield_max_charges: 3,
+        ..Default::default()
+    };
+    let mut power_ups = PowerUps::default();
+    power_ups.apply(pickup(PowerUpKind::Shield, 0.0), &config);
+    assert_eq!(power_ups.active()[0].charges, 2);
+    power_ups.apply(pickup(PowerUpKind::Shield, 0.0), &config);
+    assert_eq!(power_ups.active()[0].charges, 3);
+}
+
+#[test]
+fn shield_ends_on_its_last_charge() {
+    let config = PowerUpConfig {
+        shield_charges: 2,
+        ..Default::default()
+    };
+    let mut power_ups = PowerUps::default();
+    assert!(!power_ups.use_shield());
+    power_ups.apply(pickup(PowerUpKind::Shield, 0.0), &config);
+
+    assert!(power_ups.use_shield());
+    assert!(power_ups.is_active(PowerUpKind::Shield));
+    assert!(power_ups.use_shield());
+    assert!(!power_ups.is_active(PowerUpKind::Shield));
+    assert!(!power_ups.use_shield());
+}
@@ -0,0 +1,70 @@
+// Save files written and read back as text, without touching the saves dir.
+use engine::assets::load_assets_headless;
+use engine::generator::{generate_scene_for_seed, GameRules};
+use engine::save::{decode_save, encode_save, SaveGame};
+use macroquad::prelude::*;
+
+// Floats whose text serde_json doesn't always parse back exactly, plus
+// denormals and the extremes.
+const AWKWARD_FLOATS: [f32; 6] = [
+    9.948361e-31,
+    1.0e-40,
+    f32::MIN_POSITIVE,
+    f32::MAX,
+    -3.4028e38,
+    1.2345678e25,
+];
+
+fn awkward_save() -> SaveGame {
+    let assets = load_assets_headless("assets").unwrap();
+    let rules = GameRules::default();
+    let world_size = vec2(1280.0 * 7.0, 720.0 * 14.0);
+    let scene = generate_scene_for_seed(&assets, &rules, 1, world_size, 7);
+    let mut snapshot = scene.snapshot();
+    let floats = AWKWARD_FLOATS.iter().cycle();
+    for (entity, &x) in snapshot.entities.iter_mut().zip(floats) {
+        entity.velocity = [x, -x];
+        entity.momentum = [x * 0.5, x];
+    }
+    snapshot.time = AWKWARD_FLOATS[0];
+    SaveGame {
+        run_time: AWKWARD_FLOATS[1],
+        scene: Some(snapshot),
+        ..SaveGame::new(&rules, 7, 1, world_size.to_array())
+    }
+}
+
+fn float_bits(save: &SaveGame) -> Vec<u32> {
+    let snapshot = save.scene.as_ref().unwrap();
+    let mut bits = vec![save.run_time.to_bits(), snapshot.time.to_bits()];
+    for entity in &snapshot.entities {
+        bits.extend(entity.velocity.iter().map(|v| v.to_bits()));
+        bits.extend(entity.momentum.iter().map(|v| v.to_bits()));
+    }
+    bits
+}
+
+#[test]
+fn save_round_trips_awkward_floats() {
+    let save = awkward_save();
+    let text = encode_save(&save).unwrap();
+    let loaded = decode_save(&text).unwrap();
+    assert_eq!(float_bits(&save), float_bits(&loaded));
+    // A second trip through the file must still pass its checksum.
+    let again = decode_save(&encode_save(&loaded).unwrap()).unwrap();
+    assert_eq!(float_bits(&save), float_bits(&again));
+}
+
+#[test]
+fn damaged_saves_are_refused() {
+    let mut save = awkward_save();
+    save.run_score = 42;
+    let text = encode_save(&save).unwrap();
+
+    let edited = text.replacen("\"run_score\":42", "\"run_score\":43", 1);
+    assert_ne!(edited, text);
+    assert!(decode_save(&edited).unwrap_err().contains("checksum"));
+    assert!(decode_save(&text[..text.len() / 2]).is_err());
+    let newer = text.replacen("\"version\": 1", "\"version\": 999", 1);
+    assert!(decode_save(&newer).unwrap_err().contains("newer version"));
+}